@@ -0,0 +1,24 @@
+mod access;
+pub use access::*;
+mod ast;
+pub use ast::*;
+mod fingerprint;
+pub use fingerprint::*;
+mod fork;
+pub use fork::*;
+mod inheritance;
+pub use inheritance::*;
+mod metrics;
+pub use metrics::*;
+mod proxy;
+pub use proxy::*;
+mod reading_order;
+pub use reading_order::*;
+mod selectors;
+pub use selectors::*;
+mod similarity;
+pub use similarity::*;
+mod standards;
+pub use standards::*;
+mod storage;
+pub use storage::*;
@@ -0,0 +1,777 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::types::Contract;
+
+use super::{
+    child_nodes, external_call_member, find_nodes, function_definitions, function_signature,
+    linearized_definitions, node_id, node_name, node_str, node_type, walk_nodes, SourceAsts,
+};
+
+/// An externally callable function and what guards it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FunctionSurface {
+    pub signature: String,
+    /// Contract of the most derived declaration
+    pub declared_in: String,
+    pub visibility: String,
+    pub state_mutability: String,
+    pub modifiers: Vec<String>,
+    /// Roles allowed to call the function, empty when anyone can
+    pub roles: BTreeSet<String>,
+    /// Checks on the caller, e.g. `msg.sender == owner` or `hasRole(MINTER_ROLE)`
+    pub sender_checks: BTreeSet<String>,
+    /// Checks on `tx.origin`, e.g. `tx.origin == msg.sender`, which are not access control
+    pub origin_checks: BTreeSet<String>,
+    /// External calls made directly or through internal functions and modifiers, e.g.
+    /// `IERC20.transfer` or `address.call`
+    pub external_calls: BTreeSet<String>,
+    /// Calls sending ether
+    pub value_transfers: BTreeSet<String>,
+}
+
+impl FunctionSurface {
+    pub fn is_permissioned(&self) -> bool {
+        !self.roles.is_empty()
+    }
+}
+
+/// Externally callable functions of a contract, including inherited ones, sorted by signature
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CallSurface {
+    pub functions: Vec<FunctionSurface>,
+}
+
+impl CallSurface {
+    /// Roles required by each function, `anyone` when the function is not permissioned
+    pub fn permission_matrix(&self) -> BTreeMap<String, Vec<String>> {
+        self.functions
+            .iter()
+            .map(|function| {
+                let roles = if function.roles.is_empty() {
+                    vec!["anyone".to_owned()]
+                } else {
+                    function.roles.iter().cloned().collect()
+                };
+                (function.signature.clone(), roles)
+            })
+            .collect()
+    }
+
+    /// Markdown table of the permission matrix
+    pub fn to_markdown(&self) -> String {
+        let mut result = "| Function | Roles | External calls |\n| --- | --- | --- |\n".to_owned();
+        for (function, roles) in self.functions.iter().zip(self.permission_matrix().values()) {
+            let external_calls: Vec<&str> = function
+                .external_calls
+                .iter()
+                .map(|call| call.as_str())
+                .collect();
+            result.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                function.signature,
+                roles.join(", "),
+                external_calls.join(", ")
+            ));
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Findings {
+    roles: BTreeSet<String>,
+    sender_checks: BTreeSet<String>,
+    origin_checks: BTreeSet<String>,
+    external_calls: BTreeSet<String>,
+    value_transfers: BTreeSet<String>,
+}
+
+/// Build the call surface and permission matrix of contracts from the AST
+#[derive(Debug, Clone)]
+pub struct AccessResolver<'a> {
+    asts: &'a SourceAsts,
+    /// Contract definitions by compilation job and id, to follow `linearizedBaseContracts`
+    contracts: BTreeMap<(&'a str, usize), &'a Value>,
+    /// Function and modifier definitions by compilation job and id, to follow internal calls
+    callables: BTreeMap<(&'a str, usize), &'a Value>,
+}
+
+impl<'a> AccessResolver<'a> {
+    pub fn new(asts: &'a SourceAsts) -> Self {
+        let mut callables = BTreeMap::new();
+        for (job, ast) in asts.source_units() {
+            walk_nodes(ast, &mut |node| {
+                if matches!(
+                    node_type(node),
+                    Some("FunctionDefinition" | "ModifierDefinition")
+                ) {
+                    if let Some(id) = node_id(node) {
+                        callables.insert((job, id), node);
+                    }
+                }
+            });
+        }
+        Self {
+            asts,
+            contracts: asts.contracts_by_id(),
+            callables,
+        }
+    }
+
+    pub fn surface_of(&self, contract: &Contract) -> CallSurface {
+        let job = self.asts.job(&contract.source_file);
+        let definitions = match self
+            .asts
+            .contract_definition(&contract.source_file, &contract.name)
+        {
+            Some(definition) => linearized_definitions(&self.contracts, job, definition),
+            None => vec![],
+        };
+
+        let mut functions: BTreeMap<String, FunctionSurface> = BTreeMap::new();
+        for definition in definitions {
+            let name = node_name(definition).unwrap_or_default();
+            for function in function_definitions(definition) {
+                let is_callable = matches!(
+                    node_str(function, "kind"),
+                    Some("function" | "fallback" | "receive")
+                ) && matches!(
+                    node_str(function, "visibility"),
+                    Some("external" | "public")
+                );
+                if !is_callable {
+                    continue;
+                }
+                let signature = function_signature(function);
+                if functions.contains_key(&signature) {
+                    // already overridden by a more derived contract
+                    continue;
+                }
+                functions.insert(
+                    signature.clone(),
+                    self.function_surface(job, name, function),
+                );
+            }
+            // public state variables are callable through their getters
+            for variable in child_nodes(definition).filter(|node| {
+                node_type(node) == Some("VariableDeclaration")
+                    && node_str(node, "visibility") == Some("public")
+            }) {
+                functions
+                    .entry(getter_signature(variable))
+                    .or_insert_with_key(|signature| FunctionSurface {
+                        signature: signature.clone(),
+                        declared_in: name.to_owned(),
+                        visibility: "public".to_owned(),
+                        state_mutability: "view".to_owned(),
+                        ..Default::default()
+                    });
+            }
+        }
+
+        CallSurface {
+            functions: functions.into_values().collect(),
+        }
+    }
+
+    fn function_surface(&self, job: &str, declared_in: &str, function: &Value) -> FunctionSurface {
+        let mut findings = Findings::default();
+        let mut visited: BTreeSet<usize> = BTreeSet::new();
+        let mut modifiers: Vec<String> = vec![];
+
+        for invocation in function
+            .get("modifiers")
+            .and_then(|modifiers| modifiers.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = invocation
+                .pointer("/modifierName/name")
+                .and_then(|name| name.as_str())
+            else {
+                continue;
+            };
+            modifiers.push(name.to_owned());
+
+            let roles_before = findings.roles.len();
+            // arguments such as the role of `onlyRole(MINTER_ROLE)`
+            if let Some(argument) = invocation
+                .get("arguments")
+                .and_then(|arguments| arguments.as_array())
+                .and_then(|arguments| arguments.first())
+            {
+                if name.to_lowercase().contains("role") {
+                    findings.roles.insert(describe(argument));
+                    findings
+                        .sender_checks
+                        .insert(format!("{}({})", name, describe(argument)));
+                }
+            }
+            let modifier = invocation
+                .pointer("/modifierName/referencedDeclaration")
+                .and_then(|id| id.as_u64())
+                .and_then(|id| self.callables.get(&(job, id as usize)));
+            // the body of `onlyRole(role)` would only yield its parameter name
+            let has_role_argument = findings.roles.len() > roles_before;
+            if let (Some(modifier), false) = (modifier, has_role_argument) {
+                self.visit(job, modifier, &mut findings, &mut visited);
+            }
+            if findings.roles.len() == roles_before {
+                if let Some(role) = name.strip_prefix("only").filter(|role| !role.is_empty()) {
+                    findings.roles.insert(lower_first(role));
+                    findings.sender_checks.insert(format!("modifier {}", name));
+                }
+            }
+        }
+        self.visit(job, function, &mut findings, &mut visited);
+
+        FunctionSurface {
+            signature: function_signature(function),
+            declared_in: declared_in.to_owned(),
+            visibility: node_str(function, "visibility")
+                .unwrap_or_default()
+                .to_owned(),
+            state_mutability: node_str(function, "stateMutability")
+                .unwrap_or_default()
+                .to_owned(),
+            modifiers,
+            roles: findings.roles,
+            sender_checks: findings.sender_checks,
+            origin_checks: findings.origin_checks,
+            external_calls: findings.external_calls,
+            value_transfers: findings.value_transfers,
+        }
+    }
+
+    /// Collect checks and calls of a function or modifier body of the compilation job `job`,
+    /// following internal calls
+    fn visit(
+        &self,
+        job: &str,
+        callable: &Value,
+        findings: &mut Findings,
+        visited: &mut BTreeSet<usize>,
+    ) {
+        if let Some(id) = node_id(callable) {
+            if !visited.insert(id) {
+                return;
+            }
+        }
+        let Some(body) = callable.get("body") else {
+            return;
+        };
+
+        let mut internal_calls: Vec<&Value> = vec![];
+        for node in find_nodes(body, "BinaryOperation") {
+            if !matches!(node_str(node, "operator"), Some("==" | "!=")) {
+                continue;
+            }
+            let (Some(left), Some(right)) =
+                (node.get("leftExpression"), node.get("rightExpression"))
+            else {
+                continue;
+            };
+            if is_origin(left) || is_origin(right) {
+                let (origin, other) = if is_origin(left) {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                findings.origin_checks.insert(format!(
+                    "{} == {}",
+                    caller_name(origin),
+                    caller_name(other)
+                ));
+                continue;
+            }
+            let (sender, other) = if is_sender(left) {
+                (left, right)
+            } else if is_sender(right) {
+                (right, left)
+            } else {
+                continue;
+            };
+            let role = describe(other);
+            findings
+                .sender_checks
+                .insert(format!("{} == {}", caller_name(sender), role));
+            findings.roles.insert(role);
+        }
+        for node in find_nodes(body, "IndexAccess") {
+            // `require(isAdmin[msg.sender])`
+            let is_bool = node
+                .pointer("/typeDescriptions/typeString")
+                .and_then(|type_string| type_string.as_str())
+                == Some("bool");
+            let Some(index) = node.get("indexExpression").filter(|_| is_bool) else {
+                continue;
+            };
+            if is_origin(index) {
+                if let Some(base) = node.get("baseExpression") {
+                    findings
+                        .origin_checks
+                        .insert(format!("{}[tx.origin]", describe(base)));
+                }
+                continue;
+            }
+            if !is_sender(index) {
+                continue;
+            }
+            if let Some(base) = node.get("baseExpression") {
+                let role = describe(base);
+                findings
+                    .sender_checks
+                    .insert(format!("{}[msg.sender]", role));
+                findings.roles.insert(role);
+            }
+        }
+        for call in find_nodes(body, "FunctionCall") {
+            let callee = call.get("expression").map(describe).unwrap_or_default();
+            let arguments: Vec<&Value> = call
+                .get("arguments")
+                .and_then(|arguments| arguments.as_array())
+                .into_iter()
+                .flatten()
+                .collect();
+            let lower_callee = callee.to_lowercase();
+
+            if lower_callee.contains("role") && !arguments.is_empty() {
+                // `hasRole(ROLE, msg.sender)`, `_checkRole(ROLE)`
+                if arguments.iter().any(|argument| is_sender(argument)) || arguments.len() == 1 {
+                    let role = describe(arguments[0]);
+                    findings
+                        .sender_checks
+                        .insert(format!("{}({})", callee, role));
+                    findings.roles.insert(role);
+                }
+            } else if (lower_callee.contains("auth") || lower_callee.contains("whitelist"))
+                && arguments.iter().any(|argument| is_sender(argument))
+            {
+                findings
+                    .sender_checks
+                    .insert(format!("{}(msg.sender)", callee));
+                findings.roles.insert(callee.clone());
+            }
+
+            if let Some(member) = external_call_member(call) {
+                let target = call_target(call);
+                findings
+                    .external_calls
+                    .insert(format!("{}.{}", target, member));
+                if matches!(member, "transfer" | "send") && target == "address" {
+                    findings
+                        .value_transfers
+                        .insert(format!("{}.{}", target, member));
+                }
+            }
+            if has_value_option(call) {
+                findings
+                    .value_transfers
+                    .insert(format!("{}.{}", call_target(call), callee));
+            }
+            if callee == "selfdestruct" {
+                findings.value_transfers.insert("selfdestruct".to_owned());
+            }
+
+            let referenced = call
+                .pointer("/expression/referencedDeclaration")
+                .and_then(|id| id.as_u64())
+                .and_then(|id| self.callables.get(&(job, id as usize)));
+            if let Some(referenced) = referenced {
+                internal_calls.push(referenced);
+            }
+        }
+        for modifier in find_nodes(body, "ModifierInvocation") {
+            if let Some(referenced) = modifier
+                .pointer("/modifierName/referencedDeclaration")
+                .and_then(|id| id.as_u64())
+                .and_then(|id| self.callables.get(&(job, id as usize)))
+            {
+                internal_calls.push(referenced);
+            }
+        }
+
+        for callee in internal_calls {
+            self.visit(job, callee, findings, visited);
+        }
+    }
+}
+
+/// `msg.sender` or `_msgSender()`
+fn is_sender(expression: &Value) -> bool {
+    match node_type(expression) {
+        Some("MemberAccess") => is_member(expression, "msg", "sender"),
+        Some("FunctionCall") => {
+            expression
+                .pointer("/expression/name")
+                .and_then(|name| name.as_str())
+                == Some("_msgSender")
+        }
+        _ => false,
+    }
+}
+
+/// `tx.origin`
+fn is_origin(expression: &Value) -> bool {
+    node_type(expression) == Some("MemberAccess") && is_member(expression, "tx", "origin")
+}
+
+fn is_member(expression: &Value, base: &str, member: &str) -> bool {
+    expression
+        .pointer("/expression/name")
+        .and_then(|name| name.as_str())
+        == Some(base)
+        && node_str(expression, "memberName") == Some(member)
+}
+
+/// `msg.sender`, `tx.origin` or `_msgSender()`, otherwise the short name of the expression
+fn caller_name(expression: &Value) -> String {
+    if is_sender(expression) || is_origin(expression) {
+        match node_type(expression) {
+            Some("MemberAccess") => format!(
+                "{}.{}",
+                expression
+                    .pointer("/expression/name")
+                    .and_then(|name| name.as_str())
+                    .unwrap_or_default(),
+                node_str(expression, "memberName").unwrap_or_default()
+            ),
+            _ => "_msgSender()".to_owned(),
+        }
+    } else {
+        describe(expression)
+    }
+}
+
+/// Getter signature of a public state variable, with a parameter per mapping key or array index
+fn getter_signature(variable: &Value) -> String {
+    let mut parameters: Vec<&str> = vec![];
+    let mut type_name = variable.get("typeName");
+    while let Some(current) = type_name {
+        match node_type(current) {
+            Some("Mapping") => {
+                parameters.extend(
+                    current
+                        .pointer("/keyType/typeDescriptions/typeString")
+                        .and_then(|type_string| type_string.as_str()),
+                );
+                type_name = current.get("valueType");
+            }
+            Some("ArrayTypeName") => {
+                parameters.push("uint256");
+                type_name = current.get("baseType");
+            }
+            _ => break,
+        }
+    }
+    format!(
+        "{}({})",
+        node_name(variable).unwrap_or_default(),
+        parameters.join(",")
+    )
+}
+
+/// Short name of an expression: the identifier, member or called function name
+fn describe(expression: &Value) -> String {
+    match node_type(expression) {
+        Some("Identifier") => node_name(expression).unwrap_or_default().to_owned(),
+        Some("MemberAccess") => node_str(expression, "memberName")
+            .unwrap_or_default()
+            .to_owned(),
+        Some("FunctionCall") | Some("FunctionCallOptions") => expression
+            .get("expression")
+            .map(describe)
+            .unwrap_or_default(),
+        Some("IndexAccess") => expression
+            .get("baseExpression")
+            .map(describe)
+            .unwrap_or_default(),
+        _ => node_str(expression, "value").unwrap_or("?").to_owned(),
+    }
+}
+
+/// Contract name or `address` of the target of an external call
+fn call_target(call: &Value) -> String {
+    let type_string = [
+        "/expression/expression/typeDescriptions/typeString",
+        "/expression/expression/expression/typeDescriptions/typeString",
+    ]
+    .iter()
+    .find_map(|pointer| call.pointer(pointer).and_then(|value| value.as_str()))
+    .unwrap_or_default();
+    match type_string.strip_prefix("contract ") {
+        Some(name) => name.to_owned(),
+        None if type_string.starts_with("address") => "address".to_owned(),
+        None => type_string.to_owned(),
+    }
+}
+
+/// `{value: ...}` call option
+fn has_value_option(call: &Value) -> bool {
+    let Some(expression) = call.get("expression") else {
+        return false;
+    };
+    node_type(expression) == Some("FunctionCallOptions")
+        && expression
+            .get("names")
+            .and_then(|names| names.as_array())
+            .is_some_and(|names| names.iter().any(|name| name == "value"))
+}
+
+fn lower_first(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{ContractBuilder, ContractKind};
+    use semver::Version;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn contract(source_file: &str, name: &str) -> Contract {
+        ContractBuilder::default()
+            .name(name.to_owned())
+            .kind(ContractKind::Interface)
+            .version(Version::new(0, 8, 19))
+            .source_file(PathBuf::from(source_file))
+            .build()
+            .unwrap()
+    }
+
+    fn msg_sender() -> Value {
+        json!({
+            "nodeType": "MemberAccess", "memberName": "sender",
+            "expression": { "nodeType": "Identifier", "name": "msg" }
+        })
+    }
+
+    fn function(id: usize, name: &str, modifiers: Value, statements: Value) -> Value {
+        json!({
+            "id": id, "nodeType": "FunctionDefinition", "name": name, "kind": "function",
+            "visibility": "external", "stateMutability": "nonpayable",
+            "parameters": { "parameters": [] }, "modifiers": modifiers,
+            "body": { "nodeType": "Block", "statements": statements }
+        })
+    }
+
+    fn source_unit() -> Value {
+        json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                {
+                    "id": 10, "nodeType": "ContractDefinition", "name": "Ownable",
+                    "linearizedBaseContracts": [10], "nodes": [
+                        {
+                            "id": 1, "nodeType": "ModifierDefinition", "name": "onlyOwner",
+                            "body": { "nodeType": "Block", "statements": [{
+                                "nodeType": "BinaryOperation", "operator": "==",
+                                "leftExpression": msg_sender(),
+                                "rightExpression": { "nodeType": "Identifier", "name": "owner" }
+                            }]}
+                        }
+                    ]
+                },
+                {
+                    "id": 20, "nodeType": "ContractDefinition", "name": "Vault",
+                    "linearizedBaseContracts": [20, 10], "nodes": [
+                        function(2, "sweep", json!([{
+                            "nodeType": "ModifierInvocation",
+                            "modifierName": { "name": "onlyOwner", "referencedDeclaration": 1 }
+                        }]), json!([{
+                            "nodeType": "FunctionCall", "kind": "functionCall",
+                            "expression": {
+                                "nodeType": "MemberAccess", "memberName": "transfer",
+                                "expression": {
+                                    "nodeType": "Identifier", "name": "token",
+                                    "typeDescriptions": { "typeString": "contract IERC20" }
+                                }
+                            },
+                            "arguments": []
+                        }])),
+                        function(3, "pause", json!([{
+                            "nodeType": "ModifierInvocation",
+                            "modifierName": { "name": "onlyRole" },
+                            "arguments": [{ "nodeType": "Identifier", "name": "PAUSER_ROLE" }]
+                        }]), json!([])),
+                        function(4, "deposit", json!([]), json!([]))
+                    ]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_call_surface() {
+        let mut asts = SourceAsts::default();
+        asts.insert("src/Vault.sol", source_unit());
+        let resolver = AccessResolver::new(&asts);
+        let contract = contract("src/Vault.sol", "Vault");
+
+        let surface = resolver.surface_of(&contract);
+
+        assert_eq!(
+            surface.permission_matrix(),
+            BTreeMap::from([
+                ("deposit()".to_owned(), vec!["anyone".to_owned()]),
+                ("pause()".to_owned(), vec!["PAUSER_ROLE".to_owned()]),
+                ("sweep()".to_owned(), vec!["owner".to_owned()]),
+            ])
+        );
+        let sweep = &surface.functions[2];
+        assert_eq!(sweep.modifiers, vec!["onlyOwner"]);
+        assert!(sweep.sender_checks.contains("msg.sender == owner"));
+        assert!(sweep.external_calls.contains("IERC20.transfer"));
+        assert!(surface
+            .to_markdown()
+            .contains("| `sweep()` | owner | IERC20.transfer |"));
+    }
+
+    #[test]
+    fn test_call_surface_across_jobs() {
+        // `onlyGovernance` reuses the id of `onlyOwner` in another compilation job
+        let mut asts = SourceAsts::default();
+        asts.insert_from_job("src/Vault.sol", "0.8.19", source_unit());
+        asts.insert_from_job(
+            "src/Zap.sol",
+            "0.7.6",
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [{
+                    "id": 20, "nodeType": "ContractDefinition", "name": "Zap", "nodes": [{
+                        "id": 1, "nodeType": "ModifierDefinition", "name": "onlyGovernance",
+                        "body": { "nodeType": "Block", "statements": [{
+                            "nodeType": "BinaryOperation", "operator": "==",
+                            "leftExpression": msg_sender(),
+                            "rightExpression": { "nodeType": "Identifier", "name": "governance" }
+                        }]}
+                    }]
+                }]
+            }),
+        );
+        let resolver = AccessResolver::new(&asts);
+        let contract = contract("src/Vault.sol", "Vault");
+
+        let surface = resolver.surface_of(&contract);
+
+        let sweep = &surface.functions[2];
+        assert_eq!(sweep.roles, BTreeSet::from(["owner".to_owned()]));
+        assert!(sweep.sender_checks.contains("msg.sender == owner"));
+    }
+
+    #[test]
+    fn test_call_surface_getters_and_origin_checks() {
+        let tx_origin = json!({
+            "nodeType": "MemberAccess", "memberName": "origin",
+            "expression": { "nodeType": "Identifier", "name": "tx" }
+        });
+        let mut asts = SourceAsts::default();
+        asts.insert(
+            "src/Pool.sol",
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [{
+                    "id": 30, "nodeType": "ContractDefinition", "name": "Pool", "nodes": [
+                        {
+                            "nodeType": "VariableDeclaration", "name": "owner",
+                            "visibility": "public", "stateVariable": true,
+                            "typeName": { "nodeType": "ElementaryTypeName", "name": "address" }
+                        },
+                        {
+                            "nodeType": "VariableDeclaration", "name": "balances",
+                            "visibility": "public", "stateVariable": true,
+                            "typeName": {
+                                "nodeType": "Mapping",
+                                "keyType": {
+                                    "nodeType": "ElementaryTypeName",
+                                    "typeDescriptions": { "typeString": "address" }
+                                },
+                                "valueType": { "nodeType": "ElementaryTypeName", "name": "uint256" }
+                            }
+                        },
+                        {
+                            "nodeType": "VariableDeclaration", "name": "secret",
+                            "visibility": "private", "stateVariable": true,
+                            "typeName": { "nodeType": "ElementaryTypeName", "name": "uint256" }
+                        },
+                        function(1, "skim", json!([]), json!([
+                            {
+                                "nodeType": "BinaryOperation", "operator": "==",
+                                "leftExpression": tx_origin,
+                                "rightExpression": msg_sender()
+                            },
+                            {
+                                "nodeType": "IndexAccess",
+                                "typeDescriptions": { "typeString": "bool" },
+                                "baseExpression": { "nodeType": "Identifier", "name": "allowed" },
+                                "indexExpression": tx_origin
+                            }
+                        ]))
+                    ]
+                }]
+            }),
+        );
+        let resolver = AccessResolver::new(&asts);
+        let contract = contract("src/Pool.sol", "Pool");
+
+        let surface = resolver.surface_of(&contract);
+
+        assert_eq!(
+            surface.permission_matrix(),
+            BTreeMap::from([
+                ("balances(address)".to_owned(), vec!["anyone".to_owned()]),
+                ("owner()".to_owned(), vec!["anyone".to_owned()]),
+                ("skim()".to_owned(), vec!["anyone".to_owned()]),
+            ])
+        );
+        assert_eq!(surface.functions[0].state_mutability, "view");
+        let skim = &surface.functions[2];
+        assert!(skim.sender_checks.is_empty());
+        assert_eq!(
+            skim.origin_checks,
+            BTreeSet::from([
+                "allowed[tx.origin]".to_owned(),
+                "tx.origin == msg.sender".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_call_surface_of_duplicate_names() {
+        // a library `Vault` sorts before the project one and shares its name
+        let mut asts = SourceAsts::default();
+        asts.insert("src/Vault.sol", source_unit());
+        asts.insert(
+            "lib/vendor/Vault.sol",
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [{
+                    "id": 40, "nodeType": "ContractDefinition", "name": "Vault",
+                    "linearizedBaseContracts": [40],
+                    "nodes": [function(41, "withdraw", json!([]), json!([]))]
+                }]
+            }),
+        );
+        let resolver = AccessResolver::new(&asts);
+
+        let surface = resolver.surface_of(&contract("src/Vault.sol", "Vault"));
+        let signatures: Vec<&str> = surface
+            .functions
+            .iter()
+            .map(|function| function.signature.as_str())
+            .collect();
+        assert_eq!(signatures, vec!["deposit()", "pause()", "sweep()"]);
+
+        let surface = resolver.surface_of(&contract("lib/vendor/Vault.sol", "Vault"));
+        assert_eq!(surface.functions.len(), 1);
+        assert_eq!(surface.functions[0].signature, "withdraw()");
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use ethers_solc::{cache::SolFilesCache, ArtifactOutput, ConfigurableArtifacts, Project};
+use serde_json::Value;
+
+/// The AST of every source file of a project, as JSON so that node attributes not typed by
+/// `ethers_solc::artifacts::Ast` can be read. Keyed by source file relative to the project root.
+#[derive(Debug, Clone, Default)]
+pub struct SourceAsts {
+    pub files: BTreeMap<PathBuf, Value>,
+    /// Compilation job each AST was read from. Node ids are only unique within a job
+    pub jobs: BTreeMap<PathBuf, String>,
+}
+
+impl SourceAsts {
+    /// Read the AST of each file in the solc cache from an artifact of the newest solc version
+    /// it was compiled with, which is the compilation job of the file
+    pub fn from_cache<P>(solc_cache: &SolFilesCache, project_root: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let project_root = project_root.as_ref();
+        let mut asts = Self::default();
+        for (file, entry) in solc_cache.files.iter() {
+            let Some((version, artifact_file)) = entry
+                .artifacts
+                .values()
+                .flat_map(|artifacts| artifacts.iter())
+                .max_by_key(|(version, _)| *version)
+            else {
+                continue;
+            };
+            let Some(ast) = Project::<ConfigurableArtifacts>::read_cached_artifact(artifact_file)
+                .ok()
+                .and_then(|artifact| artifact.ast)
+                .and_then(|ast| serde_json::to_value(ast).ok())
+            else {
+                continue;
+            };
+            let file = file.strip_prefix(project_root).unwrap_or(file);
+            asts.insert_from_job(file, &version.to_string(), ast);
+        }
+        asts
+    }
+
+    pub fn insert<P>(&mut self, source_file: P, ast: Value)
+    where
+        P: AsRef<Path>,
+    {
+        self.insert_from_job(source_file, "", ast);
+    }
+
+    /// Insert the AST of `source_file` compiled in the compilation job `job`
+    pub fn insert_from_job<P>(&mut self, source_file: P, job: &str, ast: Value)
+    where
+        P: AsRef<Path>,
+    {
+        let source_file = source_file.as_ref().to_path_buf();
+        self.jobs.insert(source_file.clone(), job.to_owned());
+        self.files.insert(source_file, ast);
+    }
+
+    pub fn get<P>(&self, source_file: P) -> Option<&Value>
+    where
+        P: AsRef<Path>,
+    {
+        self.files.get(source_file.as_ref())
+    }
+
+    /// Compilation job of the AST of `source_file`
+    pub fn job<P>(&self, source_file: P) -> &str
+    where
+        P: AsRef<Path>,
+    {
+        self.jobs
+            .get(source_file.as_ref())
+            .map(|job| job.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Every AST with the compilation job it was read from
+    pub fn source_units(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.files
+            .iter()
+            .map(|(source_file, ast)| (self.job(source_file), ast))
+    }
+
+    /// The `ContractDefinition` node named `name` in `source_file`
+    pub fn contract_definition<P>(&self, source_file: P, name: &str) -> Option<&Value>
+    where
+        P: AsRef<Path>,
+    {
+        contract_definitions(self.get(source_file)?)
+            .into_iter()
+            .find(|contract| node_name(contract) == Some(name))
+    }
+
+    /// `ContractDefinition` nodes of every AST by compilation job and node id
+    pub fn contracts_by_id(&self) -> BTreeMap<(&str, usize), &Value> {
+        self.source_units()
+            .flat_map(|(job, ast)| {
+                contract_definitions(ast)
+                    .into_iter()
+                    .filter_map(move |contract| Some(((job, node_id(contract)?), contract)))
+            })
+            .collect()
+    }
+}
+
+/// `contract` followed by its bases, most derived first, following the `linearizedBaseContracts`
+/// ids of the compilation job `job`. Bases missing from `contracts` are skipped.
+pub fn linearized_definitions<'a>(
+    contracts: &BTreeMap<(&'a str, usize), &'a Value>,
+    job: &'a str,
+    contract: &'a Value,
+) -> Vec<&'a Value> {
+    let id = node_id(contract);
+    let bases = contract
+        .get("linearizedBaseContracts")
+        .and_then(|ids| ids.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|base_id| base_id.as_u64().map(|base_id| base_id as usize))
+        .filter(|base_id| Some(*base_id) != id)
+        .filter_map(|base_id| contracts.get(&(job, base_id)).copied());
+    std::iter::once(contract).chain(bases).collect()
+}
+
+pub fn node_type(node: &Value) -> Option<&str> {
+    node.get("nodeType")?.as_str()
+}
+
+pub fn node_id(node: &Value) -> Option<usize> {
+    node.get("id")?.as_u64().map(|id| id as usize)
+}
+
+pub fn node_name(node: &Value) -> Option<&str> {
+    node.get("name")?.as_str()
+}
+
+pub fn node_str<'a>(node: &'a Value, key: &str) -> Option<&'a str> {
+    node.get(key)?.as_str()
+}
+
+/// Visit `node` and every AST node nested in it, depth first
+pub fn walk_nodes<'a, F>(node: &'a Value, visit: &mut F)
+where
+    F: FnMut(&'a Value),
+{
+    match node {
+        Value::Object(map) => {
+            if map.contains_key("nodeType") {
+                visit(node);
+            }
+            for value in map.values() {
+                walk_nodes(value, visit);
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter() {
+                walk_nodes(item, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Every node of type `node_type` nested in `node`, including `node` itself
+pub fn find_nodes<'a>(node: &'a Value, node_type_name: &str) -> Vec<&'a Value> {
+    let mut result: Vec<&'a Value> = vec![];
+    walk_nodes(node, &mut |item| {
+        if node_type(item) == Some(node_type_name) {
+            result.push(item);
+        }
+    });
+    result
+}
+
+/// Top level `ContractDefinition` nodes of a source unit
+pub fn contract_definitions(source_unit: &Value) -> Vec<&Value> {
+    child_nodes(source_unit)
+        .filter(|node| node_type(node) == Some("ContractDefinition"))
+        .collect()
+}
+
+/// Direct `nodes` children of a source unit or a contract definition
+pub fn child_nodes(node: &Value) -> impl Iterator<Item = &Value> {
+    node.get("nodes")
+        .and_then(|nodes| nodes.as_array())
+        .into_iter()
+        .flatten()
+}
+
+/// `FunctionDefinition` nodes declared in a contract definition
+pub fn function_definitions(contract: &Value) -> Vec<&Value> {
+    child_nodes(contract)
+        .filter(|node| node_type(node) == Some("FunctionDefinition"))
+        .collect()
+}
+
+/// Parameter types of a function, event or error definition
+pub fn parameter_types(definition: &Value) -> Vec<String> {
+    definition
+        .pointer("/parameters/parameters")
+        .and_then(|parameters| parameters.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|parameter| {
+            parameter
+                .pointer("/typeDescriptions/typeString")
+                .and_then(|type_string| type_string.as_str())
+                .map(|type_string| type_string.to_owned())
+        })
+        .collect()
+}
+
+/// Signature of a function definition, e.g. `transfer(address,uint256)`. Constructor, fallback
+/// and receive functions are named after their kind.
+pub fn function_signature(function: &Value) -> String {
+    let name = match node_name(function) {
+        Some(name) if !name.is_empty() => name,
+        _ => node_str(function, "kind").unwrap_or_default(),
+    };
+    format!("{}({})", name, parameter_types(function).join(","))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use std::fs;
+
+    /// Load the AST of an artifact under `tests/artifacts`
+    pub(crate) fn load_test_ast(artifact_name: &str) -> Value {
+        let content =
+            fs::read_to_string(format!("tests/artifacts/{}.json", artifact_name)).unwrap();
+        let artifact: Value = serde_json::from_str(&content).unwrap();
+        artifact["ast"].clone()
+    }
+
+    #[test]
+    fn test_function_signatures() {
+        let ast = load_test_ast("ERC20");
+        let contract = contract_definitions(&ast)[0];
+        let signatures: Vec<String> = function_definitions(contract)
+            .into_iter()
+            .map(function_signature)
+            .collect();
+
+        assert_eq!(node_name(contract), Some("ERC20"));
+        assert!(signatures.contains(&"constructor(string,string,uint8)".to_owned()));
+        assert!(signatures.contains(&"transfer(address,uint256)".to_owned()));
+        assert!(find_nodes(&ast, "FunctionDefinition").len() >= signatures.len());
+    }
+}
@@ -0,0 +1,372 @@
+use ethers::utils::{hex, keccak256};
+use serde::Serialize;
+
+use super::strip_comments;
+
+/// A function, modifier, constructor, fallback or receive function found in Solidity source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceFunction {
+    /// `name(types)` with the parameter types, unique within a contract and kept when
+    /// parameters are renamed
+    pub key: String,
+    pub name: String,
+    /// Source of the whole definition, without comments
+    pub text: String,
+    /// `text` without formatting differences
+    pub normalized: String,
+    /// keccak256 of `normalized`
+    pub hash: String,
+}
+
+/// A contract, library or interface found in Solidity source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceContract {
+    pub name: String,
+    pub functions: Vec<SourceFunction>,
+}
+
+impl SourceContract {
+    pub fn function(&self, key: &str) -> Option<&SourceFunction> {
+        self.functions.iter().find(|function| function.key == key)
+    }
+}
+
+/// Split Solidity source into contracts and their functions. This works on the text so that
+/// sources which can't be compiled, such as an upstream corpus, are handled the same way.
+pub fn parse_source(source: &str) -> Vec<SourceContract> {
+    let code: Vec<char> = strip_comments(source).chars().collect();
+    let mut contracts: Vec<SourceContract> = vec![];
+
+    let mut index = 0;
+    while index < code.len() {
+        if let Some(end) = skip_string(&code, index) {
+            index = end;
+            continue;
+        }
+        let Some(keyword) = keyword_at(&code, index, &["contract", "library", "interface"]) else {
+            index += 1;
+            continue;
+        };
+        let name_start = skip_whitespace(&code, index + keyword.len());
+        let name = identifier_at(&code, name_start);
+        let open = find_outside_strings(&code, name_start, &['{', ';']);
+        match (name.is_empty(), open) {
+            (false, Some(open)) if code[open] == '{' => {
+                let Some(close) = matching_brace(&code, open) else {
+                    break;
+                };
+                contracts.push(SourceContract {
+                    name,
+                    functions: parse_members(&code, open + 1, close),
+                });
+                index = close + 1;
+            }
+            _ => index += keyword.len(),
+        }
+    }
+    contracts
+}
+
+fn parse_members(code: &[char], start: usize, end: usize) -> Vec<SourceFunction> {
+    let mut functions: Vec<SourceFunction> = vec![];
+    let mut depth = 0;
+
+    let mut index = start;
+    while index < end {
+        if let Some(next) = skip_string(code, index) {
+            index = next;
+            continue;
+        }
+        match code[index] {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        let keyword = if depth == 0 {
+            keyword_at(
+                code,
+                index,
+                &["function", "modifier", "constructor", "fallback", "receive"],
+            )
+        } else {
+            None
+        };
+        let Some(keyword) = keyword else {
+            index += 1;
+            continue;
+        };
+
+        let name = match keyword {
+            "function" | "modifier" => {
+                identifier_at(code, skip_whitespace(code, index + keyword.len()))
+            }
+            _ => keyword.to_owned(),
+        };
+        let Some(body_start) = find_outside_strings(code, index, &['{', ';']) else {
+            break;
+        };
+        if code[body_start] == ';' {
+            // declaration without implementation
+            index = body_start + 1;
+            continue;
+        }
+        let Some(body_end) = matching_brace(code, body_start) else {
+            break;
+        };
+
+        let params: String = code[index..body_start]
+            .iter()
+            .position(|c| *c == '(')
+            .and_then(|offset| {
+                let open = index + offset;
+                matching_paren(code, open).map(|close| code[open + 1..close].iter().collect())
+            })
+            .unwrap_or_default();
+        let text: String = code[index..=body_end].iter().collect();
+        let normalized = normalize_code(&text);
+        functions.push(SourceFunction {
+            key: format!("{}({})", name, parameter_types(&params)),
+            name,
+            hash: format!("0x{}", hex::encode(keccak256(normalized.as_bytes()))),
+            normalized,
+            text,
+        });
+        index = body_end + 1;
+    }
+    functions
+}
+
+/// Remove comments and whitespace that doesn't separate two words
+pub fn normalize_code(code: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut pending_space = false;
+    for c in strip_comments(code).chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && result.chars().last().is_some_and(is_word_char) && is_word_char(c) {
+            result.push(' ');
+        }
+        pending_space = false;
+        result.push(c);
+    }
+    result
+}
+
+/// Types of a parameter list, without data locations and parameter names, e.g. `uint256,bytes`
+/// for `uint256 amount, bytes calldata data`
+fn parameter_types(params: &str) -> String {
+    let params = normalize_code(params);
+    let mut types: Vec<String> = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                types.push(parameter_type(&params[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if !params.trim().is_empty() {
+        types.push(parameter_type(&params[start..]));
+    }
+    types.join(",")
+}
+
+fn parameter_type(param: &str) -> String {
+    let param = param
+        .split(' ')
+        .filter(|word| !matches!(*word, "memory" | "storage" | "calldata" | ""))
+        .collect::<Vec<_>>()
+        .join(" ");
+    // the trailing word is a name unless it's the whole type or part of it, as in `address payable`
+    let type_end = param.trim_end_matches(is_word_char).trim_end().len();
+    if type_end == 0 || param.ends_with("payable") {
+        param
+    } else {
+        param[..type_end].to_owned()
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// End of the string literal starting at `index`, if any
+fn skip_string(code: &[char], index: usize) -> Option<usize> {
+    let delimiter = code[index];
+    if delimiter != '"' && delimiter != '\'' {
+        return None;
+    }
+    let mut current = index + 1;
+    while current < code.len() {
+        match code[current] {
+            '\\' => current += 2,
+            c if c == delimiter || c == '\n' => return Some(current + 1),
+            _ => current += 1,
+        }
+    }
+    Some(code.len())
+}
+
+fn keyword_at(code: &[char], index: usize, keywords: &[&'static str]) -> Option<&'static str> {
+    if index > 0 && is_word_char(code[index - 1]) {
+        return None;
+    }
+    keywords.iter().copied().find(|keyword| {
+        let end = index + keyword.len();
+        end <= code.len()
+            && code[index..end].iter().copied().eq(keyword.chars())
+            && code.get(end).is_none_or(|c| !is_word_char(*c))
+    })
+}
+
+fn skip_whitespace(code: &[char], mut index: usize) -> usize {
+    while index < code.len() && code[index].is_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+fn identifier_at(code: &[char], index: usize) -> String {
+    code[index.min(code.len())..]
+        .iter()
+        .take_while(|c| is_word_char(**c))
+        .collect()
+}
+
+/// First of `targets` at or after `index`, outside string literals
+fn find_outside_strings(code: &[char], mut index: usize, targets: &[char]) -> Option<usize> {
+    while index < code.len() {
+        if let Some(next) = skip_string(code, index) {
+            index = next;
+            continue;
+        }
+        if targets.contains(&code[index]) {
+            return Some(index);
+        }
+        index += 1;
+    }
+    None
+}
+
+fn matching_brace(code: &[char], open: usize) -> Option<usize> {
+    matching(code, open, '{', '}')
+}
+
+fn matching_paren(code: &[char], open: usize) -> Option<usize> {
+    matching(code, open, '(', ')')
+}
+
+fn matching(code: &[char], open: usize, open_char: char, close_char: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = open;
+    while index < code.len() {
+        if let Some(next) = skip_string(code, index) {
+            index = next;
+            continue;
+        }
+        if code[index] == open_char {
+            depth += 1;
+        } else if code[index] == close_char {
+            depth -= 1;
+            if depth == 0 {
+                return Some(index);
+            }
+        }
+        index += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.19;
+
+interface IPool {
+    function swap(uint256 amount) external returns (uint256);
+}
+
+/// @notice a pool
+contract Pool is IPool {
+    struct Slot { uint256 price; }
+    string constant NAME = "contract { fake }";
+
+    constructor() {}
+
+    modifier lock() {
+        _;
+    }
+
+    function swap(uint256 amount) external lock returns (uint256) {
+        if (amount == 0) { revert(); } // no-op
+        return amount;
+    }
+
+    function swap(uint256 amount, address to) external returns (uint256) { return amount; }
+}
+"#;
+
+    #[test]
+    fn test_parse_source() {
+        let contracts = parse_source(SOURCE);
+
+        assert_eq!(contracts.len(), 2);
+        assert_eq!(contracts[0].name, "IPool");
+        assert!(contracts[0].functions.is_empty());
+        let keys: Vec<&str> = contracts[1]
+            .functions
+            .iter()
+            .map(|function| function.key.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "constructor()",
+                "lock()",
+                "swap(uint256)",
+                "swap(uint256,address)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_function_key_ignores_parameter_names() {
+        let original = "contract A { function f(uint256 amount, bytes memory data) external {} }";
+        let renamed = "contract A { function f(uint256 amt, bytes memory) external {} }";
+
+        assert_eq!(
+            parse_source(original)[0].functions[0].key,
+            "f(uint256,bytes)"
+        );
+        assert_eq!(
+            parse_source(renamed)[0].functions[0].key,
+            "f(uint256,bytes)"
+        );
+        assert_eq!(
+            parameter_types("address payable to, function(uint256) external returns (bool) f"),
+            "address payable,function(uint256)external returns(bool)"
+        );
+    }
+
+    #[test]
+    fn test_normalize_code() {
+        let a = "function f(uint256 a) external {\n    return a + 1; // add\n}";
+        let b = "function f( uint256 a )external{return a+1;}";
+
+        assert_eq!(normalize_code(a), normalize_code(b));
+        assert_eq!(
+            normalize_code(b),
+            "function f(uint256 a)external{return a+1;}"
+        );
+    }
+}
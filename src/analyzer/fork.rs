@@ -0,0 +1,322 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::{errors::AppError, types::Contract};
+
+use super::{parse_source, SourceContract, SourceFunction};
+
+/// Contracts of known protocols, read from `<corpus>/<protocol>/**/*.sol`
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamCorpus {
+    pub contracts: Vec<UpstreamContract>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpstreamContract {
+    /// Name of the top level directory, e.g. `uniswap-v3-core`
+    pub protocol: String,
+    /// Source file, relative to the corpus directory
+    pub source_file: PathBuf,
+    pub contract: SourceContract,
+}
+
+impl UpstreamCorpus {
+    pub fn load<P>(corpus_dir: P) -> Result<Self, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        let corpus_dir = corpus_dir.as_ref();
+        let mut contracts: Vec<UpstreamContract> = vec![];
+        for entry in WalkDir::new(corpus_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "sol"))
+        {
+            let source_file = entry
+                .path()
+                .strip_prefix(corpus_dir)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let Some(protocol) = source_file
+                .components()
+                .next()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let source = fs::read_to_string(entry.path())?;
+            contracts.extend(
+                parse_source(&source)
+                    .into_iter()
+                    .filter(|contract| !contract.functions.is_empty())
+                    .map(|contract| UpstreamContract {
+                        protocol: protocol.clone(),
+                        source_file: source_file.clone(),
+                        contract,
+                    }),
+            );
+        }
+        Ok(Self { contracts })
+    }
+}
+
+/// Diff of a function changed from its upstream version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionDiff {
+    pub key: String,
+    /// Line diff, lines prefixed by `-`, `+` or a space
+    pub diff: String,
+}
+
+/// A contest contract matched to the upstream contract it was forked from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForkMatch {
+    pub contract: String,
+    pub protocol: String,
+    pub upstream_contract: String,
+    pub upstream_file: PathBuf,
+    /// Share of functions, from both sides, that are identical after normalization
+    pub similarity: f64,
+    pub identical: Vec<String>,
+    pub changed: Vec<FunctionDiff>,
+    /// Functions only in the contest contract
+    pub added: Vec<String>,
+    /// Upstream functions the contest contract dropped
+    pub removed: Vec<String>,
+}
+
+impl Display for ForkMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is {:.0}% identical to {} ({})",
+            self.contract,
+            self.similarity * 100.0,
+            self.upstream_contract,
+            self.protocol
+        )
+    }
+}
+
+/// Match contest contracts against an [`UpstreamCorpus`]
+pub struct ForkResolver {}
+
+impl ForkResolver {
+    /// Matches below this similarity are not reported
+    pub const MIN_SIMILARITY: f64 = 0.3;
+
+    /// Best upstream match of a contract parsed from source
+    pub fn detect(contract: &SourceContract, corpus: &UpstreamCorpus) -> Option<ForkMatch> {
+        corpus
+            .contracts
+            .iter()
+            .map(|upstream| Self::compare(contract, upstream))
+            .filter(|fork| fork.similarity >= Self::MIN_SIMILARITY)
+            .max_by(|a, b| {
+                a.similarity
+                    .total_cmp(&b.similarity)
+                    // prefer the upstream contract of the same name on ties
+                    .then_with(|| {
+                        (a.upstream_contract == contract.name)
+                            .cmp(&(b.upstream_contract == contract.name))
+                    })
+            })
+    }
+
+    /// Fork matches of the contracts of a project, reading their source files
+    pub fn detect_in_project(contracts: &[Contract], corpus: &UpstreamCorpus) -> Vec<ForkMatch> {
+        let mut seen: BTreeSet<(&Path, &str)> = BTreeSet::new();
+        contracts
+            .iter()
+            .filter(|contract| seen.insert((&contract.source_file, &contract.name)))
+            .filter_map(|contract| {
+                let source =
+                    fs::read_to_string(contract.project_root.join(&contract.source_file)).ok()?;
+                let parsed = parse_source(&source)
+                    .into_iter()
+                    .find(|parsed| parsed.name == contract.name)?;
+                Self::detect(&parsed, corpus)
+            })
+            .collect()
+    }
+
+    pub fn compare(contract: &SourceContract, upstream: &UpstreamContract) -> ForkMatch {
+        let mut identical: Vec<String> = vec![];
+        let mut changed: Vec<FunctionDiff> = vec![];
+        let mut added: Vec<String> = vec![];
+        for function in contract.functions.iter() {
+            let upstream_function = upstream
+                .contract
+                .function(&function.key)
+                .or_else(|| Self::find_by_hash(&upstream.contract, function));
+            match upstream_function {
+                Some(upstream_function) if upstream_function.hash == function.hash => {
+                    identical.push(function.key.clone())
+                }
+                Some(upstream_function) => changed.push(FunctionDiff {
+                    key: function.key.clone(),
+                    diff: line_diff(&upstream_function.text, &function.text),
+                }),
+                None => added.push(function.key.clone()),
+            }
+        }
+        let removed: Vec<String> = upstream
+            .contract
+            .functions
+            .iter()
+            .filter(|upstream_function| {
+                contract.function(&upstream_function.key).is_none()
+                    && !contract
+                        .functions
+                        .iter()
+                        .any(|function| function.hash == upstream_function.hash)
+            })
+            .map(|upstream_function| upstream_function.key.clone())
+            .collect();
+
+        let total = identical.len() + changed.len() + added.len() + removed.len();
+        let similarity = if total == 0 {
+            0.0
+        } else {
+            identical.len() as f64 / total as f64
+        };
+
+        ForkMatch {
+            contract: contract.name.clone(),
+            protocol: upstream.protocol.clone(),
+            upstream_contract: upstream.contract.name.clone(),
+            upstream_file: upstream.source_file.clone(),
+            similarity,
+            identical,
+            changed,
+            added,
+            removed,
+        }
+    }
+
+    fn find_by_hash<'a>(
+        upstream: &'a SourceContract,
+        function: &SourceFunction,
+    ) -> Option<&'a SourceFunction> {
+        upstream
+            .functions
+            .iter()
+            .find(|upstream_function| upstream_function.hash == function.hash)
+    }
+}
+
+/// Line diff of `old` and `new` using their longest common subsequence, ignoring indentation
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let new: Vec<&str> = new
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    // lengths[i][j] = LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            result.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            result.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            result.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const UPSTREAM: &str = r#"
+contract UniswapV2Pair {
+    function mint(address to) external returns (uint liquidity) {
+        liquidity = 1;
+    }
+
+    function burn(address to) external returns (uint amount0, uint amount1) {
+        amount0 = 1;
+        amount1 = 1;
+    }
+
+    function skim(address to) external {
+        to;
+    }
+}
+"#;
+
+    const FORK: &str = r#"
+contract Pair {
+    // same as upstream, reformatted
+    function mint(address to) external returns (uint liquidity) { liquidity = 1; }
+
+    function burn(address recipient) external returns (uint amount0, uint amount1) {
+        amount0 = 1;
+        amount1 = 2;
+    }
+
+    function skim(address to) external {
+        to;
+    }
+}
+"#;
+
+    #[test]
+    fn test_fork_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let protocol_dir = dir.path().join("uniswap-v2-core");
+        fs::create_dir_all(&protocol_dir).unwrap();
+        fs::write(protocol_dir.join("UniswapV2Pair.sol"), UPSTREAM).unwrap();
+        let corpus = UpstreamCorpus::load(dir.path()).unwrap();
+
+        let contract = &parse_source(FORK)[0];
+        let fork = ForkResolver::detect(contract, &corpus).unwrap();
+
+        assert_eq!(fork.upstream_contract, "UniswapV2Pair");
+        assert_eq!(fork.protocol, "uniswap-v2-core");
+        assert_eq!(fork.identical, vec!["mint(address)", "skim(address)"]);
+        // renaming a parameter shows up as a change of the same function
+        assert_eq!(fork.changed.len(), 1);
+        assert_eq!(fork.changed[0].key, "burn(address)");
+        assert!(fork.added.is_empty());
+        assert!(fork.changed[0]
+            .diff
+            .contains("- amount1 = 1;\n+ amount1 = 2;\n"));
+        assert_eq!(
+            fork.to_string(),
+            "Pair is 67% identical to UniswapV2Pair (uniswap-v2-core)"
+        );
+    }
+}
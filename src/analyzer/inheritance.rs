@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::graph::{render_dot, render_mermaid};
+
+use super::{
+    contract_definitions, function_definitions, function_signature, node_id, node_name, node_str,
+    SourceAsts,
+};
+
+/// Inheritance of a single contract
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inheritance {
+    /// Bases in the order they are declared in `is A, B`
+    pub direct_bases: Vec<String>,
+    /// C3 linearization, from the contract itself to the most base contract
+    pub linearized: Vec<String>,
+    /// Functions declared by at least one base, sorted by signature
+    pub inherited_functions: Vec<InheritedFunction>,
+    /// Direct bases of every contract in the linearization
+    pub tree: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritedFunction {
+    pub signature: String,
+    /// Contracts declaring the function, in linearized order
+    pub declared_in: Vec<String>,
+    /// The most derived declaration, which is the one that gets called
+    pub resolved_in: String,
+}
+
+impl InheritedFunction {
+    /// Whether a derived contract overrides a base declaration
+    pub fn is_overridden(&self) -> bool {
+        self.declared_in.len() > 1
+    }
+}
+
+impl Inheritance {
+    pub fn to_dot(&self) -> String {
+        render_dot("inheritance", &self.tree)
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        render_mermaid(&self.tree)
+    }
+}
+
+/// Compilation job and id of an AST node, as node ids are only unique within a job
+type NodeKey = (String, usize);
+
+#[derive(Debug, Clone)]
+struct ContractNode {
+    name: String,
+    base_ids: Vec<usize>,
+    base_names: Vec<String>,
+    linearized: Vec<usize>,
+    functions: Vec<String>,
+}
+
+/// Resolve the inheritance of contracts using every `ContractDefinition` of a project
+#[derive(Debug, Clone, Default)]
+pub struct InheritanceResolver {
+    contracts: BTreeMap<NodeKey, ContractNode>,
+}
+
+impl InheritanceResolver {
+    pub fn new(asts: &SourceAsts) -> Self {
+        let mut resolver = Self::default();
+        for (job, ast) in asts.source_units() {
+            resolver.add_source_unit(job, ast);
+        }
+        resolver
+    }
+
+    /// Add the contracts of `source_unit`, compiled in the compilation job `job`
+    pub fn add_source_unit(&mut self, job: &str, source_unit: &Value) {
+        for contract in contract_definitions(source_unit) {
+            let Some(id) = node_id(contract) else {
+                continue;
+            };
+            let base_contracts: Vec<&Value> = contract
+                .get("baseContracts")
+                .and_then(|bases| bases.as_array())
+                .into_iter()
+                .flatten()
+                .collect();
+
+            let functions = function_definitions(contract)
+                .into_iter()
+                .filter(|function| {
+                    matches!(
+                        node_str(function, "kind"),
+                        Some("function" | "fallback" | "receive")
+                    )
+                })
+                .map(function_signature)
+                .collect();
+
+            self.contracts.insert(
+                (job.to_owned(), id),
+                ContractNode {
+                    name: node_name(contract).unwrap_or_default().to_owned(),
+                    base_ids: base_contracts
+                        .iter()
+                        .filter_map(|base| base.pointer("/baseName/referencedDeclaration"))
+                        .filter_map(|id| id.as_u64().map(|id| id as usize))
+                        .collect(),
+                    base_names: base_contracts
+                        .iter()
+                        .filter_map(|base| base.pointer("/baseName/name"))
+                        .filter_map(|name| name.as_str().map(|name| name.to_owned()))
+                        .collect(),
+                    linearized: contract
+                        .get("linearizedBaseContracts")
+                        .and_then(|ids| ids.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|id| id.as_u64().map(|id| id as usize))
+                        .collect(),
+                    functions,
+                },
+            );
+        }
+    }
+
+    /// Inheritance of the `ContractDefinition` node `contract` of the compilation job `job`
+    pub fn inheritance_of(&self, job: &str, contract: &Value) -> Option<Inheritance> {
+        let key = |id: &usize| (job.to_owned(), *id);
+        let id = node_id(contract)?;
+        let node = self.contracts.get(&key(&id))?;
+
+        let linearized_ids = if !node.linearized.is_empty()
+            && node
+                .linearized
+                .iter()
+                .all(|id| self.contracts.contains_key(&key(id)))
+        {
+            node.linearized.clone()
+        } else {
+            // fall back to our own C3 linearization when solc's can't be resolved
+            c3_linearize(&id, &|id: &usize| {
+                self.contracts
+                    .get(&key(id))
+                    .map(|node| node.base_ids.iter().rev().cloned().collect())
+                    .unwrap_or_default()
+            })
+            .filter(|ids| ids.iter().all(|id| self.contracts.contains_key(&key(id))))
+            .unwrap_or(vec![id])
+        };
+        let linearized: Vec<&ContractNode> = linearized_ids
+            .iter()
+            .filter_map(|id| self.contracts.get(&key(id)))
+            .collect();
+
+        let mut functions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for contract_node in linearized.iter() {
+            for signature in contract_node.functions.iter() {
+                functions
+                    .entry(signature.as_str())
+                    .or_default()
+                    .push(contract_node.name.clone());
+            }
+        }
+        let inherited_functions = functions
+            .into_iter()
+            .filter(|(_, declared_in)| declared_in.iter().any(|name| name != &node.name))
+            .map(|(signature, declared_in)| InheritedFunction {
+                signature: signature.to_owned(),
+                resolved_in: declared_in[0].clone(),
+                declared_in,
+            })
+            .collect();
+
+        let tree = linearized
+            .iter()
+            .map(|contract_node| {
+                (
+                    contract_node.name.clone(),
+                    contract_node.base_names.iter().cloned().collect(),
+                )
+            })
+            .collect();
+
+        Some(Inheritance {
+            direct_bases: node.base_names.clone(),
+            linearized: linearized
+                .iter()
+                .map(|contract_node| contract_node.name.clone())
+                .collect(),
+            inherited_functions,
+            tree,
+        })
+    }
+}
+
+/// C3 linearization of `node`. `bases` returns the direct bases of a node, most derived first,
+/// which is the reverse of the Solidity `is A, B` order. Returns `None` if no linearization exists.
+pub fn c3_linearize<N, F>(node: &N, bases: &F) -> Option<Vec<N>>
+where
+    N: Clone + PartialEq,
+    F: Fn(&N) -> Vec<N>,
+{
+    let direct_bases = bases(node);
+    let mut sequences: Vec<Vec<N>> = direct_bases
+        .iter()
+        .map(|base| c3_linearize(base, bases))
+        .collect::<Option<Vec<Vec<N>>>>()?;
+    sequences.push(direct_bases);
+
+    let mut result = vec![node.clone()];
+    loop {
+        sequences.retain(|sequence| !sequence.is_empty());
+        if sequences.is_empty() {
+            return Some(result);
+        }
+
+        let head = sequences
+            .iter()
+            .map(|sequence| &sequence[0])
+            .find(|candidate| {
+                !sequences
+                    .iter()
+                    .any(|sequence| sequence[1..].contains(candidate))
+            })?
+            .clone();
+
+        for sequence in sequences.iter_mut() {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+        result.push(head);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn contract(id: usize, name: &str, bases: &[(usize, &str)], functions: &[&str]) -> Value {
+        json!({
+            "id": id,
+            "nodeType": "ContractDefinition",
+            "name": name,
+            "baseContracts": bases.iter().map(|(id, name)| json!({
+                "baseName": { "name": name, "referencedDeclaration": id }
+            })).collect::<Vec<Value>>(),
+            "nodes": functions.iter().map(|name| json!({
+                "nodeType": "FunctionDefinition",
+                "kind": "function",
+                "name": name,
+                "parameters": { "parameters": [] }
+            })).collect::<Vec<Value>>(),
+        })
+    }
+
+    #[test]
+    fn test_c3_linearize() {
+        // contract A; contract B is A; contract C is A; contract D is B, C
+        let bases = |node: &&str| -> Vec<&str> {
+            match *node {
+                "B" | "C" => vec!["A"],
+                "D" => vec!["C", "B"],
+                _ => vec![],
+            }
+        };
+
+        assert_eq!(c3_linearize(&"D", &bases), Some(vec!["D", "C", "B", "A"]));
+    }
+
+    #[test]
+    fn test_inheritance_resolver() {
+        let source_unit = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                contract(1, "Ownable", &[], &["owner", "transferOwnership"]),
+                contract(2, "Pausable", &[], &["pause"]),
+                contract(3, "Vault", &[(1, "Ownable"), (2, "Pausable")], &["pause", "deposit"]),
+            ]
+        });
+        let mut resolver = InheritanceResolver::default();
+        resolver.add_source_unit("", &source_unit);
+
+        let inheritance = resolver
+            .inheritance_of("", &source_unit["nodes"][2])
+            .unwrap();
+
+        assert_eq!(inheritance.direct_bases, vec!["Ownable", "Pausable"]);
+        assert_eq!(inheritance.linearized, vec!["Vault", "Pausable", "Ownable"]);
+        assert_eq!(
+            inheritance.inherited_functions,
+            vec![
+                InheritedFunction {
+                    signature: "owner()".to_owned(),
+                    declared_in: vec!["Ownable".to_owned()],
+                    resolved_in: "Ownable".to_owned(),
+                },
+                InheritedFunction {
+                    signature: "pause()".to_owned(),
+                    declared_in: vec!["Vault".to_owned(), "Pausable".to_owned()],
+                    resolved_in: "Vault".to_owned(),
+                },
+                InheritedFunction {
+                    signature: "transferOwnership()".to_owned(),
+                    declared_in: vec!["Ownable".to_owned()],
+                    resolved_in: "Ownable".to_owned(),
+                },
+            ]
+        );
+        assert!(inheritance.to_mermaid().contains("flowchart LR"));
+    }
+
+    #[test]
+    fn test_inheritance_resolver_across_jobs() {
+        // node ids restart in every compilation job, so `Vault` and `Token` both extend id 1
+        let mut asts = SourceAsts::default();
+        asts.insert_from_job(
+            "src/Vault.sol",
+            "0.8.19",
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [
+                    contract(1, "Ownable", &[], &["owner"]),
+                    contract(2, "Vault", &[(1, "Ownable")], &["deposit"]),
+                ]
+            }),
+        );
+        asts.insert_from_job(
+            "src/Token.sol",
+            "0.7.6",
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [
+                    contract(1, "ERC20", &[], &["transfer"]),
+                    contract(2, "Token", &[(1, "ERC20")], &["mint"]),
+                ]
+            }),
+        );
+        let resolver = InheritanceResolver::new(&asts);
+
+        let vault = asts.contract_definition("src/Vault.sol", "Vault").unwrap();
+        let token = asts.contract_definition("src/Token.sol", "Token").unwrap();
+
+        assert_eq!(
+            resolver
+                .inheritance_of(asts.job("src/Vault.sol"), vault)
+                .unwrap()
+                .linearized,
+            vec!["Vault", "Ownable"]
+        );
+        assert_eq!(
+            resolver
+                .inheritance_of(asts.job("src/Token.sol"), token)
+                .unwrap()
+                .linearized,
+            vec!["Token", "ERC20"]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use std::{
+    ops::{Add, AddAssign},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{find_nodes, node_str, node_type, walk_nodes};
+
+/// Size and complexity metrics of a contract or a source file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// Source lines of code, without comments and blank lines
+    pub nsloc: usize,
+    pub external_functions: usize,
+    pub public_functions: usize,
+    pub modifiers: usize,
+    pub assembly_blocks: usize,
+    pub external_calls: usize,
+    /// Sum of the cyclomatic complexity of every function and modifier
+    pub cyclomatic_complexity: usize,
+}
+
+impl Add for Metrics {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            nsloc: self.nsloc + other.nsloc,
+            external_functions: self.external_functions + other.external_functions,
+            public_functions: self.public_functions + other.public_functions,
+            modifiers: self.modifiers + other.modifiers,
+            assembly_blocks: self.assembly_blocks + other.assembly_blocks,
+            external_calls: self.external_calls + other.external_calls,
+            cyclomatic_complexity: self.cyclomatic_complexity + other.cyclomatic_complexity,
+        }
+    }
+}
+
+impl AddAssign for Metrics {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::iter::Sum for Metrics {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, metrics| total + metrics)
+    }
+}
+
+/// Metrics of a whole source file, relative to the project root
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileMetrics {
+    pub source_file: PathBuf,
+    pub metrics: Metrics,
+}
+
+/// Compute [`Metrics`] from the AST and the source text
+pub struct MetricsResolver {}
+
+impl MetricsResolver {
+    /// Metrics of a `ContractDefinition` node. `source` is the content of its source file,
+    /// used to count nSLOC.
+    pub fn contract_metrics(definition: &Value, source: Option<&str>) -> Metrics {
+        let mut metrics = Self::ast_metrics(definition);
+        metrics.nsloc = source
+            .and_then(|source| source_slice(source, definition))
+            .map(count_nsloc)
+            .unwrap_or_default();
+        metrics
+    }
+
+    /// Metrics of a `SourceUnit` node, including free functions
+    pub fn source_unit_metrics(source_unit: &Value, source: Option<&str>) -> Metrics {
+        let mut metrics = Self::ast_metrics(source_unit);
+        metrics.nsloc = source.map(count_nsloc).unwrap_or_default();
+        metrics
+    }
+
+    fn ast_metrics(node: &Value) -> Metrics {
+        let mut metrics = Metrics::default();
+
+        for function in find_nodes(node, "FunctionDefinition") {
+            match node_str(function, "visibility") {
+                Some("external") => metrics.external_functions += 1,
+                Some("public") => metrics.public_functions += 1,
+                _ => {}
+            }
+            metrics.cyclomatic_complexity += cyclomatic_complexity(function);
+        }
+        for modifier in find_nodes(node, "ModifierDefinition") {
+            metrics.modifiers += 1;
+            metrics.cyclomatic_complexity += cyclomatic_complexity(modifier);
+        }
+        metrics.assembly_blocks = find_nodes(node, "InlineAssembly").len();
+        metrics.external_calls = find_nodes(node, "FunctionCall")
+            .into_iter()
+            .filter(|call| external_call_member(call).is_some())
+            .count();
+
+        metrics
+    }
+}
+
+/// Member name of a call on a contract or an address, e.g. `transfer` in `token.transfer(to, 1)`
+/// or `call` in `to.call{value: 1}("")`
+pub fn external_call_member(call: &Value) -> Option<&str> {
+    if node_str(call, "kind") != Some("functionCall") {
+        return None;
+    }
+    let mut expression = call.get("expression")?;
+    if node_type(expression) == Some("FunctionCallOptions") {
+        expression = expression.get("expression")?;
+    }
+    if node_type(expression) != Some("MemberAccess") {
+        return None;
+    }
+
+    let base_type = expression
+        .pointer("/expression/typeDescriptions/typeString")?
+        .as_str()?;
+    let member_name = node_str(expression, "memberName")?;
+    let is_external = base_type.starts_with("contract ")
+        || (base_type.starts_with("address")
+            && matches!(
+                member_name,
+                "call" | "delegatecall" | "staticcall" | "send" | "transfer"
+            ));
+    is_external.then_some(member_name)
+}
+
+/// 1 plus the number of decision points of a function or modifier body
+pub fn cyclomatic_complexity(function: &Value) -> usize {
+    let Some(body) = function.get("body") else {
+        return 1;
+    };
+
+    let mut complexity = 1;
+    walk_nodes(body, &mut |node| match node_type(node) {
+        Some(
+            "IfStatement" | "ForStatement" | "WhileStatement" | "DoWhileStatement" | "Conditional",
+        ) => complexity += 1,
+        Some("BinaryOperation") if matches!(node_str(node, "operator"), Some("&&" | "||")) => {
+            complexity += 1
+        }
+        Some("TryStatement") => {
+            let clauses = node
+                .get("clauses")
+                .and_then(|clauses| clauses.as_array())
+                .map_or(0, |clauses| clauses.len());
+            complexity += clauses.saturating_sub(1);
+        }
+        _ => {}
+    });
+    complexity
+}
+
+/// Part of the source covered by a node, from its `src` attribute `start:length:file`
+fn source_slice<'a>(source: &'a str, node: &Value) -> Option<&'a str> {
+    let mut location = node_str(node, "src")?.split(':');
+    let start: usize = location.next()?.parse().ok()?;
+    let length: usize = location.next()?.parse().ok()?;
+    source.get(start..start + length)
+}
+
+/// Count the lines that still have code once comments are removed
+pub fn count_nsloc(source: &str) -> usize {
+    strip_comments(source)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+/// Remove line and block comments, keeping string literals and line breaks
+pub fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut string_delimiter: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        if let Some(delimiter) = string_delimiter {
+            stripped.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    stripped.push(escaped);
+                }
+            } else if c == delimiter || c == '\n' {
+                string_delimiter = None;
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while let Some(next) = chars.peek() {
+                    if *next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        // keep line breaks so that code around the comment stays on its own line
+                        stripped.push('\n');
+                    }
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            ('"' | '\'', _) => {
+                string_delimiter = Some(c);
+                stripped.push(c);
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analyzer::{contract_definitions, test::load_test_ast};
+    use serde_json::json;
+
+    #[test]
+    fn test_count_nsloc() {
+        let source = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.19;
+
+/// @notice docs
+contract A {
+    /* multi
+       line */ uint256 x; // trailing
+    string s = "// not a comment";
+
+    function f() external {} /* inline */
+}
+"#;
+        assert_eq!(count_nsloc(source), 6);
+    }
+
+    #[test]
+    fn test_cyclomatic_complexity() {
+        let function = json!({
+            "nodeType": "FunctionDefinition",
+            "body": {
+                "nodeType": "Block",
+                "statements": [
+                    { "nodeType": "IfStatement", "condition": {
+                        "nodeType": "BinaryOperation", "operator": "&&"
+                    }},
+                    { "nodeType": "ForStatement" },
+                    { "nodeType": "TryStatement", "clauses": [{}, {}, {}] }
+                ]
+            }
+        });
+
+        assert_eq!(cyclomatic_complexity(&function), 6);
+    }
+
+    #[test]
+    fn test_contract_metrics() {
+        let ast = load_test_ast("ERC20");
+        let definition = contract_definitions(&ast)[0];
+        let metrics = MetricsResolver::contract_metrics(definition, None);
+
+        assert_eq!(metrics.nsloc, 0);
+        assert_eq!(metrics.external_functions, 0);
+        assert_eq!(metrics.public_functions, 5);
+        assert_eq!(metrics.assembly_blocks, 0);
+        assert_eq!(metrics.external_calls, 0);
+        assert_eq!(metrics.cyclomatic_complexity, 12);
+    }
+
+    #[test]
+    fn test_contract_metrics_nsloc() {
+        let source = r#"
+pragma solidity 0.8.19;
+
+contract A {
+    uint256 a;
+}
+
+contract B {
+    // comment
+    uint256 b;
+    function f() external {}
+}
+"#;
+        let start = source.find("contract B").unwrap();
+        let end = source.rfind('}').unwrap() + 1;
+        let definition = json!({
+            "nodeType": "ContractDefinition",
+            "name": "B",
+            "src": format!("{}:{}:0", start, end - start),
+            "nodes": []
+        });
+
+        let metrics = MetricsResolver::contract_metrics(&definition, Some(source));
+        assert_eq!(metrics.nsloc, 4);
+    }
+}
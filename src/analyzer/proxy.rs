@@ -0,0 +1,483 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::types::{Contract, ContractKind};
+
+use super::{
+    child_nodes, find_nodes, function_definitions, linearized_definitions, node_name, node_str,
+    node_type, SourceAsts,
+};
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+pub const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// `bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)`
+pub const EIP1967_ADMIN_SLOT: &str =
+    "b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103";
+/// `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`
+pub const EIP1967_BEACON_SLOT: &str =
+    "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// Runtime code of an EIP-1167 minimal proxy, around the 20 bytes implementation address
+const EIP1167_RUNTIME_PREFIX: &str = "363d3d373d3d3d363d73";
+const EIP1167_RUNTIME_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+/// `diamondCut((address,uint8,bytes4[])[],address,bytes)`
+const DIAMOND_CUT_SELECTOR: &str = "0x1f931c1c";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ProxyPattern {
+    /// OpenZeppelin `TransparentUpgradeableProxy`, upgraded through an admin
+    TransparentProxy,
+    /// Implementation upgrading itself through `upgradeTo`, guarded by `_authorizeUpgrade`
+    UupsImplementation,
+    /// EIP-1967 proxy without an admin, upgraded by its UUPS implementation, e.g. `ERC1967Proxy`
+    UupsProxy,
+    /// `BeaconProxy` or `UpgradeableBeacon`
+    Beacon,
+    /// EIP-2535 diamond, dispatching to facets
+    Diamond,
+    /// Is or deploys EIP-1167 minimal proxies
+    MinimalClone,
+    /// Forwards calls with `delegatecall` without matching a known pattern
+    UnknownProxy,
+    #[default]
+    NonUpgradeable,
+}
+
+/// Upgradeability of a contract and what it was inferred from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Upgradeability {
+    pub pattern: ProxyPattern,
+    /// Whether the contract is meant to be initialized instead of constructed
+    pub initializable: bool,
+    /// Human readable reasons, e.g. `inherits UUPSUpgradeable`
+    pub evidence: Vec<String>,
+}
+
+impl Upgradeability {
+    pub fn is_upgradeable(&self) -> bool {
+        !matches!(
+            self.pattern,
+            ProxyPattern::NonUpgradeable | ProxyPattern::MinimalClone
+        )
+    }
+}
+
+/// Facts about a contract and its bases, collected from the AST
+#[derive(Debug, Clone, Default)]
+struct ProxyFacts {
+    bases: Vec<String>,
+    functions: Vec<String>,
+    modifiers: Vec<String>,
+    literals: Vec<String>,
+    /// Literals of the contract itself and of `BeaconProxy`, not of bases that only declare
+    /// the EIP-1967 slots such as `ERC1967Upgrade`
+    own_literals: Vec<String>,
+    /// Functions and modifiers declared by the contract itself
+    own_declarations: Vec<String>,
+    has_delegatecall: bool,
+    has_forwarding_fallback: bool,
+}
+
+impl ProxyFacts {
+    fn inherits(&self, name: &str) -> bool {
+        self.bases.iter().any(|base| base == name)
+    }
+
+    fn declares(&self, function: &str) -> bool {
+        self.functions.iter().any(|name| name == function)
+    }
+
+    fn declares_own(&self, name: &str) -> bool {
+        self.own_declarations.iter().any(|own| own == name)
+    }
+
+    fn uses_slot(&self, slot: &str, preimage: &str) -> bool {
+        self.literals
+            .iter()
+            .any(|literal| literal.contains(slot) || literal == preimage)
+    }
+
+    fn reads_slot(&self, slot: &str, preimage: &str) -> bool {
+        self.own_literals
+            .iter()
+            .any(|literal| literal.contains(slot) || literal == preimage)
+    }
+}
+
+/// Classify contracts by proxy and upgradeability pattern
+#[derive(Debug, Clone)]
+pub struct ProxyResolver<'a> {
+    asts: &'a SourceAsts,
+    /// Contract definitions by compilation job and id, to follow `linearizedBaseContracts`
+    contracts: BTreeMap<(&'a str, usize), &'a Value>,
+}
+
+impl<'a> ProxyResolver<'a> {
+    pub fn new(asts: &'a SourceAsts) -> Self {
+        Self {
+            asts,
+            contracts: asts.contracts_by_id(),
+        }
+    }
+
+    /// Upgradeability of `contract`, using the definitions of its linearized bases, its
+    /// selectors and its bytecode
+    pub fn upgradeability_of(&self, contract: &Contract) -> Upgradeability {
+        let facts = self.facts(contract);
+        let mut evidence: Vec<String> = vec![];
+
+        // explicit bases first, `ERC1967Upgrade` declares every EIP-1967 slot
+        let pattern = if facts.inherits("TransparentUpgradeableProxy") {
+            evidence.push("inherits TransparentUpgradeableProxy".to_owned());
+            ProxyPattern::TransparentProxy
+        } else if facts.inherits("UUPSUpgradeable") {
+            evidence.push("inherits UUPSUpgradeable".to_owned());
+            ProxyPattern::UupsImplementation
+        } else if facts.declares("_authorizeUpgrade") {
+            evidence.push("declares _authorizeUpgrade".to_owned());
+            ProxyPattern::UupsImplementation
+        } else if facts.inherits("Diamond") {
+            evidence.push("inherits Diamond".to_owned());
+            ProxyPattern::Diamond
+        } else if facts.has_forwarding_fallback
+            && (facts.declares("diamondCut") || has_selector(contract, DIAMOND_CUT_SELECTOR))
+        {
+            evidence.push("forwards calls and declares diamondCut".to_owned());
+            ProxyPattern::Diamond
+        } else if facts.inherits("BeaconProxy")
+            || facts.inherits("UpgradeableBeacon")
+            || facts.reads_slot(EIP1967_BEACON_SLOT, "eip1967.proxy.beacon")
+        {
+            evidence.push("uses an EIP-1967 beacon".to_owned());
+            ProxyPattern::Beacon
+        } else if facts.has_forwarding_fallback
+            && (facts.reads_slot(EIP1967_ADMIN_SLOT, "eip1967.proxy.admin")
+                || facts.declares_own("ifAdmin")
+                || facts.declares_own("_admin"))
+        {
+            // `ERC1967Upgrade` declares the admin slot for UUPS proxies too
+            evidence.push("forwards calls and guards them with an EIP-1967 admin".to_owned());
+            ProxyPattern::TransparentProxy
+        } else if facts.has_forwarding_fallback
+            && facts.uses_slot(EIP1967_IMPLEMENTATION_SLOT, "eip1967.proxy.implementation")
+        {
+            evidence
+                .push("forwards calls to an EIP-1967 implementation without an admin".to_owned());
+            ProxyPattern::UupsProxy
+        } else if is_minimal_clone(contract) {
+            evidence.push("contains EIP-1167 runtime code".to_owned());
+            ProxyPattern::MinimalClone
+        } else if facts.has_forwarding_fallback {
+            evidence.push("forwards calls from its fallback with delegatecall".to_owned());
+            ProxyPattern::UnknownProxy
+        } else {
+            ProxyPattern::NonUpgradeable
+        };
+
+        if facts.uses_slot(EIP1967_IMPLEMENTATION_SLOT, "eip1967.proxy.implementation") {
+            evidence.push("uses the EIP-1967 implementation slot".to_owned());
+        }
+        if facts.has_delegatecall && !facts.has_forwarding_fallback {
+            evidence.push("uses delegatecall".to_owned());
+        }
+
+        let initializable = facts.inherits("Initializable")
+            || facts.modifiers.iter().any(|name| name == "initializer")
+            || facts.declares("initialize");
+        if initializable {
+            evidence.push("is initializable".to_owned());
+        }
+
+        Upgradeability {
+            pattern,
+            initializable,
+            evidence,
+        }
+    }
+
+    fn facts(&self, contract: &Contract) -> ProxyFacts {
+        let definitions = match self
+            .asts
+            .contract_definition(&contract.source_file, &contract.name)
+        {
+            Some(definition) => linearized_definitions(
+                &self.contracts,
+                self.asts.job(&contract.source_file),
+                definition,
+            ),
+            None => vec![],
+        };
+
+        let mut facts = ProxyFacts {
+            bases: definitions
+                .iter()
+                .skip(1)
+                .filter_map(|definition| node_name(definition))
+                .map(|name| name.to_owned())
+                .collect(),
+            ..Default::default()
+        };
+        for (index, definition) in definitions.into_iter().enumerate() {
+            let is_own = index == 0 || node_name(definition) == Some("BeaconProxy");
+            if index == 0 {
+                facts.own_declarations = child_nodes(definition)
+                    .filter(|node| {
+                        matches!(
+                            node_type(node),
+                            Some("FunctionDefinition" | "ModifierDefinition")
+                        )
+                    })
+                    .filter_map(|node| node_name(node))
+                    .map(|name| name.to_owned())
+                    .collect();
+            }
+            for function in function_definitions(definition) {
+                let name = node_name(function).unwrap_or_default();
+                facts.functions.push(name.to_owned());
+
+                let is_fallback = node_str(function, "kind") == Some("fallback");
+                if is_fallback && has_delegatecall(function) {
+                    facts.has_forwarding_fallback = true;
+                }
+                // OpenZeppelin proxies forward from `_fallback` and `_delegate`
+                if matches!(name, "_fallback" | "_delegate") && has_delegatecall(function) {
+                    facts.has_forwarding_fallback = true;
+                }
+            }
+            for modifier in find_nodes(definition, "ModifierInvocation") {
+                if let Some(name) = modifier
+                    .pointer("/modifierName/name")
+                    .and_then(|name| name.as_str())
+                {
+                    facts.modifiers.push(name.to_owned());
+                }
+            }
+            for literal in find_nodes(definition, "Literal")
+                .into_iter()
+                .chain(find_nodes(definition, "YulLiteral"))
+            {
+                if let Some(value) = node_str(literal, "value") {
+                    if is_own {
+                        facts.own_literals.push(value.to_lowercase());
+                    }
+                    facts.literals.push(value.to_lowercase());
+                }
+            }
+            facts.has_delegatecall |= has_delegatecall(definition);
+        }
+        facts
+    }
+}
+
+/// Whether `node` calls `delegatecall`, in Solidity or in inline assembly
+pub fn has_delegatecall(node: &Value) -> bool {
+    find_nodes(node, "MemberAccess")
+        .into_iter()
+        .any(|member| node_str(member, "memberName") == Some("delegatecall"))
+        || find_nodes(node, "YulFunctionCall").into_iter().any(|call| {
+            call.pointer("/functionName/name")
+                .and_then(|name| name.as_str())
+                == Some("delegatecall")
+        })
+        // older solc versions only keep the assembly source
+        || find_nodes(node, "InlineAssembly").into_iter().any(|assembly| {
+            node_str(assembly, "operations")
+                .is_some_and(|operations| operations.contains("delegatecall("))
+        })
+}
+
+fn has_selector(contract: &Contract, selector: &str) -> bool {
+    contract
+        .selectors
+        .as_ref()
+        .is_some_and(|selectors| selectors.find(selector).is_some())
+}
+
+fn is_minimal_clone(contract: &Contract) -> bool {
+    let ContractKind::Contract(bytecode) = &contract.kind else {
+        return false;
+    };
+    let bytecode = bytecode.to_string().to_lowercase();
+    bytecode.contains(EIP1167_RUNTIME_PREFIX) && bytecode.contains(EIP1167_RUNTIME_SUFFIX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{ContractBuilder, ContractBytecode};
+    use semver::Version;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn contract(source_file: &str, name: &str, bytecode: &str) -> Contract {
+        ContractBuilder::default()
+            .name(name.to_owned())
+            .kind(ContractKind::Contract(ContractBytecode::from(
+                bytecode.to_owned(),
+            )))
+            .version(Version::new(0, 8, 19))
+            .source_file(PathBuf::from(source_file))
+            .build()
+            .unwrap()
+    }
+
+    fn definition(id: usize, name: &str, bases: &[usize], nodes: Value) -> Value {
+        let linearized: Vec<usize> = std::iter::once(id).chain(bases.iter().cloned()).collect();
+        json!({
+            "id": id, "nodeType": "ContractDefinition", "name": name,
+            "linearizedBaseContracts": linearized, "nodes": nodes
+        })
+    }
+
+    fn slot(name: &str, value: &str) -> Value {
+        json!({
+            "nodeType": "VariableDeclaration", "name": name,
+            "value": { "nodeType": "Literal", "value": format!("0x{value}") }
+        })
+    }
+
+    fn forwarding(name: &str, kind: &str) -> Value {
+        json!({
+            "nodeType": "FunctionDefinition", "name": name, "kind": kind,
+            "body": { "nodeType": "Block", "statements": [{
+                "nodeType": "InlineAssembly",
+                "AST": { "nodeType": "YulFunctionCall", "functionName": {
+                    "nodeType": "YulIdentifier", "name": "delegatecall"
+                }}
+            }]}
+        })
+    }
+
+    #[test]
+    fn test_proxy_patterns() {
+        let source_unit = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                definition(1, "Vault", &[], json!([
+                    {
+                        "nodeType": "FunctionDefinition", "name": "_authorizeUpgrade",
+                        "kind": "function", "modifiers": []
+                    },
+                    {
+                        "nodeType": "FunctionDefinition", "name": "initialize", "kind": "function",
+                        "modifiers": [{
+                            "nodeType": "ModifierInvocation",
+                            "modifierName": { "nodeType": "IdentifierPath", "name": "initializer" }
+                        }]
+                    }
+                ])),
+                // a project proxy named like the OpenZeppelin base
+                definition(2, "Proxy", &[], json!([
+                    forwarding("", "fallback"),
+                    slot("ADMIN_SLOT", EIP1967_ADMIN_SLOT)
+                ])),
+                definition(3, "Token", &[], json!([])),
+                definition(5, "UUPSUpgradeable", &[4], json!([
+                    {
+                        "nodeType": "FunctionDefinition", "name": "_authorizeUpgrade",
+                        "kind": "function", "modifiers": []
+                    }
+                ])),
+                definition(6, "Beacon", &[], json!([
+                    {
+                        "nodeType": "FunctionDefinition", "name": "", "kind": "fallback",
+                        "body": { "nodeType": "Block", "statements": [{
+                            "nodeType": "ExpressionStatement",
+                            "expression": { "nodeType": "MemberAccess", "memberName": "delegatecall" }
+                        }]}
+                    },
+                    slot("BEACON_SLOT", EIP1967_BEACON_SLOT)
+                ])),
+                definition(7, "DiamondCutFacet", &[], json!([
+                    { "nodeType": "FunctionDefinition", "name": "diamondCut", "kind": "function" }
+                ])),
+                definition(8, "Pool", &[5, 4], json!([])),
+                definition(13, "PoolProxy", &[10, 11, 4, 12], json!([]))
+            ]
+        });
+        // OpenZeppelin 4.x proxies
+        let openzeppelin = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                // base of both transparent proxies and UUPS implementations
+                definition(4, "ERC1967Upgrade", &[], json!([
+                    slot("_IMPLEMENTATION_SLOT", EIP1967_IMPLEMENTATION_SLOT),
+                    slot("_ADMIN_SLOT", EIP1967_ADMIN_SLOT),
+                    slot("_BEACON_SLOT", EIP1967_BEACON_SLOT)
+                ])),
+                definition(10, "TransparentUpgradeableProxy", &[11, 4, 12], json!([
+                    { "nodeType": "ModifierDefinition", "name": "ifAdmin" }
+                ])),
+                definition(11, "ERC1967Proxy", &[4, 12], json!([
+                    { "nodeType": "FunctionDefinition", "name": "", "kind": "constructor" }
+                ])),
+                definition(12, "Proxy", &[], json!([
+                    forwarding("_delegate", "function"),
+                    forwarding("", "fallback")
+                ]))
+            ]
+        });
+        let mut asts = SourceAsts::default();
+        asts.insert("src/All.sol", source_unit);
+        asts.insert("lib/openzeppelin/Proxy.sol", openzeppelin);
+        let resolver = ProxyResolver::new(&asts);
+        let of = |source_file: &str, name: &str| {
+            resolver.upgradeability_of(&contract(source_file, name, "0x6080"))
+        };
+
+        let vault = of("src/All.sol", "Vault");
+        assert_eq!(vault.pattern, ProxyPattern::UupsImplementation);
+        assert!(vault
+            .evidence
+            .contains(&"declares _authorizeUpgrade".to_owned()));
+        assert!(vault.initializable);
+        assert!(vault.is_upgradeable());
+
+        assert_eq!(
+            of("src/All.sol", "Proxy").pattern,
+            ProxyPattern::TransparentProxy
+        );
+        assert_eq!(
+            of("lib/openzeppelin/Proxy.sol", "Proxy").pattern,
+            ProxyPattern::UnknownProxy
+        );
+
+        let token = of("src/All.sol", "Token");
+        assert_eq!(token.pattern, ProxyPattern::NonUpgradeable);
+        assert!(!token.initializable);
+
+        let factory = resolver.upgradeability_of(&contract(
+            "src/Factory.sol",
+            "Factory",
+            "0x3d602d80600a3d3981f3363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3",
+        ));
+        assert_eq!(factory.pattern, ProxyPattern::MinimalClone);
+
+        let uups = of("src/All.sol", "Pool");
+        assert_eq!(uups.pattern, ProxyPattern::UupsImplementation);
+        assert!(uups
+            .evidence
+            .contains(&"inherits UUPSUpgradeable".to_owned()));
+
+        let erc1967 = of("lib/openzeppelin/Proxy.sol", "ERC1967Proxy");
+        assert_eq!(erc1967.pattern, ProxyPattern::UupsProxy);
+        assert!(erc1967.is_upgradeable());
+
+        assert_eq!(
+            of("src/All.sol", "PoolProxy").pattern,
+            ProxyPattern::TransparentProxy
+        );
+        assert_eq!(
+            of("lib/openzeppelin/Proxy.sol", "TransparentUpgradeableProxy").pattern,
+            ProxyPattern::TransparentProxy
+        );
+
+        assert_eq!(of("src/All.sol", "Beacon").pattern, ProxyPattern::Beacon);
+        assert_eq!(
+            of("src/All.sol", "DiamondCutFacet").pattern,
+            ProxyPattern::NonUpgradeable
+        );
+    }
+}
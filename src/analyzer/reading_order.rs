@@ -0,0 +1,239 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+    path::PathBuf,
+};
+
+use crate::{
+    graph::{strongly_connected_components, ImportGraph},
+    types::{Contract, ContractType},
+};
+
+/// Where a contract goes in the reading order when it has no pending dependency: interfaces,
+/// then libraries, then base contracts, then derived contracts
+fn reading_rank(contract: &Contract, base_names: &BTreeSet<&str>) -> u8 {
+    match contract.contract_type {
+        ContractType::Interface => 0,
+        ContractType::Library => 1,
+        ContractType::Abstract => 2,
+        ContractType::Contract if base_names.contains(contract.name.as_str()) => 2,
+        ContractType::Contract => 3,
+    }
+}
+
+/// Dependencies of each contract, by index: its base contracts and the contracts of the files
+/// it imports. Only contracts of the same project are linked.
+fn dependencies(
+    contracts: &[Contract],
+    import_graphs: &[ImportGraph],
+) -> BTreeMap<usize, BTreeSet<usize>> {
+    let mut by_name: BTreeMap<(&PathBuf, &str), Vec<usize>> = BTreeMap::new();
+    let mut by_file: BTreeMap<(&PathBuf, &PathBuf), Vec<usize>> = BTreeMap::new();
+    for (index, contract) in contracts.iter().enumerate() {
+        by_name
+            .entry((&contract.project_root, contract.name.as_str()))
+            .or_default()
+            .push(index);
+        by_file
+            .entry((&contract.project_root, &contract.source_file))
+            .or_default()
+            .push(index);
+    }
+
+    let mut result: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    for (index, contract) in contracts.iter().enumerate() {
+        let edges = result.entry(index).or_default();
+
+        let base_names = contract
+            .inheritance
+            .iter()
+            .flat_map(|inheritance| inheritance.linearized.iter().skip(1));
+        for base_name in base_names {
+            if let Some(bases) = by_name.get(&(&contract.project_root, base_name.as_str())) {
+                edges.extend(bases.iter().filter(|base| **base != index));
+            }
+        }
+
+        let import_graph = import_graphs
+            .iter()
+            .find(|graph| graph.project_root == contract.project_root);
+        let imported_files = import_graph
+            .map(|graph| graph.direct_imports(&contract.source_file))
+            .unwrap_or_default();
+        for file in imported_files.iter() {
+            if let Some(imported) = by_file.get(&(&contract.project_root, file)) {
+                edges.extend(imported.iter().filter(|other| **other != index));
+            }
+        }
+    }
+    result
+}
+
+/// Indexes of `contracts` in reading order: every contract comes after the contracts it
+/// inherits from or imports. Contracts in a dependency cycle are kept together and ordered by
+/// rank, name and source file so that the result is deterministic.
+pub fn reading_order(contracts: &[Contract], import_graphs: &[ImportGraph]) -> Vec<usize> {
+    let dependencies = dependencies(contracts, import_graphs);
+
+    let base_names: BTreeSet<&str> = contracts
+        .iter()
+        .flat_map(|contract| contract.inheritance.iter())
+        .flat_map(|inheritance| inheritance.linearized.iter().skip(1))
+        .map(|name| name.as_str())
+        .collect();
+    let sort_key = |index: usize| {
+        let contract = &contracts[index];
+        (
+            reading_rank(contract, &base_names),
+            contract.name.clone(),
+            contract.source_file.clone(),
+            index,
+        )
+    };
+
+    // condense cycles, then run Kahn's algorithm on the components
+    let components = strongly_connected_components(&dependencies);
+    let component_of: BTreeMap<usize, usize> = components
+        .iter()
+        .enumerate()
+        .flat_map(|(component, members)| members.iter().map(move |member| (*member, component)))
+        .collect();
+
+    let mut pending: Vec<usize> = vec![0; components.len()];
+    let mut dependents: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); components.len()];
+    for (index, edges) in dependencies.iter() {
+        let component = component_of[index];
+        for dependency in edges.iter() {
+            let dependency = component_of[dependency];
+            if dependency != component && dependents[dependency].insert(component) {
+                pending[component] += 1;
+            }
+        }
+    }
+
+    let component_key = |component: usize| {
+        components[component]
+            .iter()
+            .map(|member| sort_key(*member))
+            .min()
+            .unwrap()
+    };
+    let mut ready = BinaryHeap::new();
+    for (component, count) in pending.iter().enumerate() {
+        if *count == 0 {
+            ready.push(Reverse((component_key(component), component)));
+        }
+    }
+
+    let mut result: Vec<usize> = Vec::with_capacity(contracts.len());
+    while let Some(Reverse((_, component))) = ready.pop() {
+        let mut members = components[component].clone();
+        members.sort_by_key(|member| sort_key(*member));
+        result.extend(members);
+
+        for dependent in dependents[component].iter() {
+            pending[*dependent] -= 1;
+            if pending[*dependent] == 0 {
+                ready.push(Reverse((component_key(*dependent), *dependent)));
+            }
+        }
+    }
+    result
+}
+
+/// Sort contracts in reading order, see [`reading_order`]
+pub fn sort_by_reading_order(
+    contracts: Vec<Contract>,
+    import_graphs: &[ImportGraph],
+) -> Vec<Contract> {
+    let order = reading_order(&contracts, import_graphs);
+    let mut contracts: Vec<Option<Contract>> = contracts.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|index| contracts[index].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        analyzer::Inheritance,
+        types::{ContractBuilder, ContractKind},
+    };
+    use semver::Version;
+
+    fn contract(
+        name: &str,
+        file: &str,
+        contract_type: ContractType,
+        linearized: &[&str],
+    ) -> Contract {
+        let mut linearized: Vec<String> = linearized.iter().map(|name| name.to_string()).collect();
+        linearized.insert(0, name.to_owned());
+        ContractBuilder::default()
+            .name(name.to_owned())
+            .kind(ContractKind::Interface)
+            .contract_type(contract_type)
+            .version(Version::new(0, 8, 19))
+            .project_root(PathBuf::from("/repo"))
+            .source_file(PathBuf::from(file))
+            .inheritance(Some(Inheritance {
+                linearized,
+                ..Default::default()
+            }))
+            .build()
+            .unwrap()
+    }
+
+    fn names(contracts: &[Contract], import_graphs: &[ImportGraph]) -> Vec<String> {
+        reading_order(contracts, import_graphs)
+            .into_iter()
+            .map(|index| contracts[index].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_reading_order() {
+        let contracts = vec![
+            contract(
+                "Vault",
+                "src/Vault.sol",
+                ContractType::Contract,
+                &["Base", "IVault"],
+            ),
+            contract("Base", "src/Base.sol", ContractType::Abstract, &["IVault"]),
+            contract("Math", "src/Math.sol", ContractType::Library, &[]),
+            contract("IVault", "src/IVault.sol", ContractType::Interface, &[]),
+            contract("Router", "src/Router.sol", ContractType::Contract, &[]),
+        ];
+        let mut import_graph = ImportGraph::new("/repo");
+        import_graph.add_edge("src/Vault.sol", "src/Math.sol");
+        import_graph.add_edge("src/Router.sol", "src/Vault.sol");
+
+        assert_eq!(
+            names(&contracts, &[import_graph]),
+            vec!["IVault", "Math", "Base", "Vault", "Router"]
+        );
+    }
+
+    #[test]
+    fn test_reading_order_breaks_cycles_deterministically() {
+        let contracts = vec![
+            contract("B", "src/B.sol", ContractType::Contract, &[]),
+            contract("A", "src/A.sol", ContractType::Contract, &[]),
+            contract("IA", "src/IA.sol", ContractType::Interface, &[]),
+        ];
+        let mut import_graph = ImportGraph::new("/repo");
+        import_graph.add_edge("src/A.sol", "src/B.sol");
+        import_graph.add_edge("src/B.sol", "src/A.sol");
+        import_graph.add_edge("src/B.sol", "src/IA.sol");
+
+        let import_graphs = [import_graph];
+        assert_eq!(names(&contracts, &import_graphs), vec!["IA", "A", "B"]);
+
+        let mut reversed = contracts.clone();
+        reversed.reverse();
+        assert_eq!(names(&reversed, &import_graphs), vec!["IA", "A", "B"]);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ethers::{
+    abi::{Abi, Param, ParamType},
+    utils::{hex, keccak256},
+};
+use serde::Serialize;
+
+use crate::types::{Contract, ContractType};
+
+use super::ProxyPattern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectorKind {
+    Function,
+    Error,
+    Event,
+}
+
+/// A function or error selector, or an event topic, with its canonical signature
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Selector {
+    pub kind: SelectorKind,
+    /// `0x` prefixed, 4 bytes for functions and errors, 32 bytes for events
+    pub selector: String,
+    /// Canonical signature, e.g. `transfer(address,uint256)`
+    pub signature: String,
+}
+
+/// Functions of different contracts sharing the same 4 byte selector, see
+/// [`SelectorResolver::collisions`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelectorCollision {
+    pub kind: SelectorKind,
+    pub selector: String,
+    /// `(contract, signature)` of every declaration using the selector
+    pub declarations: Vec<(String, String)>,
+}
+
+/// Selectors and event topics of a contract ABI, sorted by kind then selector
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SelectorTable {
+    pub selectors: Vec<Selector>,
+    /// Collisions with the implementations of a proxy or across the facets of a diamond, see
+    /// [`SelectorResolver::collisions`]
+    pub collisions: Vec<SelectorCollision>,
+}
+
+impl SelectorTable {
+    pub fn from_abi(abi: &Abi) -> Self {
+        let functions = abi.functions().map(|function| {
+            Selector::new(
+                SelectorKind::Function,
+                canonical_signature(&function.name, &function.inputs),
+            )
+        });
+        let errors = abi.errors().map(|error| {
+            Selector::new(
+                SelectorKind::Error,
+                canonical_signature(&error.name, &error.inputs),
+            )
+        });
+        let events = abi.events().map(|event| {
+            let kinds: Vec<ParamType> = event
+                .inputs
+                .iter()
+                .map(|input| input.kind.clone())
+                .collect();
+            Selector::new(SelectorKind::Event, signature_of(&event.name, &kinds))
+        });
+
+        let mut selectors: Vec<Selector> = functions.chain(errors).chain(events).collect();
+        selectors.sort();
+        selectors.dedup();
+
+        Self {
+            selectors,
+            collisions: vec![],
+        }
+    }
+
+    pub fn of_kind(&self, kind: SelectorKind) -> impl Iterator<Item = &Selector> {
+        self.selectors
+            .iter()
+            .filter(move |selector| selector.kind == kind)
+    }
+
+    pub fn find(&self, selector: &str) -> Option<&Selector> {
+        self.selectors
+            .iter()
+            .find(|item| item.selector.eq_ignore_ascii_case(selector))
+    }
+}
+
+impl Selector {
+    pub fn new(kind: SelectorKind, signature: String) -> Self {
+        let hash = keccak256(signature.as_bytes());
+        let selector = match kind {
+            SelectorKind::Function | SelectorKind::Error => {
+                format!("0x{}", hex::encode(&hash[..4]))
+            }
+            SelectorKind::Event => format!("0x{}", hex::encode(hash)),
+        };
+        Self {
+            kind,
+            selector,
+            signature,
+        }
+    }
+}
+
+/// Find selector collisions between contracts whose functions are dispatched together
+pub struct SelectorResolver {}
+
+impl SelectorResolver {
+    /// Collisions between the functions of contracts sharing a dispatcher, in the same project
+    /// root as `contract`:
+    /// - a proxy and the implementations it may forward to, when their signatures differ, as
+    ///   the proxy shadows the implementation function
+    /// - the facets of a diamond, for any shared selector, as `diamondCut` rejects it
+    ///
+    /// Collisions within a linearization aren't reported, solc already rejects them.
+    /// Requires [`Contract::upgradeability`] to be resolved.
+    pub fn collisions(contract: &Contract, contracts: &[Contract]) -> Vec<SelectorCollision> {
+        let Some(pattern) = pattern_of(contract) else {
+            return vec![];
+        };
+        let siblings = contracts.iter().filter(|other| {
+            other.project_root == contract.project_root
+                && other.contract_type == ContractType::Contract
+                && other.name != contract.name
+        });
+
+        match pattern {
+            ProxyPattern::TransparentProxy
+            | ProxyPattern::UupsProxy
+            | ProxyPattern::Beacon
+            | ProxyPattern::UnknownProxy => siblings
+                .filter(|other| is_implementation(other))
+                .flat_map(|implementation| function_collisions(&[contract, implementation], false))
+                .collect(),
+            ProxyPattern::Diamond => {
+                let facets: Vec<&Contract> = siblings
+                    .filter(|other| other.name.ends_with("Facet") && pattern_of(other).is_none())
+                    .collect();
+                function_collisions(&facets, true)
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Proxy pattern of a contract dispatching calls to other contracts
+fn pattern_of(contract: &Contract) -> Option<ProxyPattern> {
+    contract
+        .upgradeability
+        .as_ref()
+        .map(|upgradeability| upgradeability.pattern)
+        .filter(|pattern| {
+            !matches!(
+                pattern,
+                ProxyPattern::NonUpgradeable
+                    | ProxyPattern::UupsImplementation
+                    | ProxyPattern::MinimalClone
+            )
+        })
+}
+
+fn is_implementation(contract: &Contract) -> bool {
+    contract
+        .upgradeability
+        .as_ref()
+        .is_some_and(|upgradeability| {
+            upgradeability.initializable
+                || upgradeability.pattern == ProxyPattern::UupsImplementation
+        })
+}
+
+/// Function selectors declared by more than one of `contracts`, with different signatures
+/// unless `same_signature` is set
+fn function_collisions(contracts: &[&Contract], same_signature: bool) -> Vec<SelectorCollision> {
+    let mut by_selector: BTreeMap<&str, BTreeSet<(&str, &str)>> = BTreeMap::new();
+    for contract in contracts {
+        let Some(table) = contract.selectors.as_ref() else {
+            continue;
+        };
+        for selector in table.of_kind(SelectorKind::Function) {
+            by_selector
+                .entry(selector.selector.as_str())
+                .or_default()
+                .insert((contract.name.as_str(), selector.signature.as_str()));
+        }
+    }
+
+    by_selector
+        .into_iter()
+        .filter(|(_, declarations)| {
+            let names: BTreeSet<&str> = declarations.iter().map(|(name, _)| *name).collect();
+            let signatures: BTreeSet<&str> = declarations
+                .iter()
+                .map(|(_, signature)| *signature)
+                .collect();
+            names.len() > 1 && (same_signature || signatures.len() > 1)
+        })
+        .map(|(selector, declarations)| SelectorCollision {
+            kind: SelectorKind::Function,
+            selector: selector.to_owned(),
+            declarations: declarations
+                .into_iter()
+                .map(|(name, signature)| (name.to_owned(), signature.to_owned()))
+                .collect(),
+        })
+        .collect()
+}
+
+fn canonical_signature(name: &str, inputs: &[Param]) -> String {
+    let kinds: Vec<ParamType> = inputs.iter().map(|input| input.kind.clone()).collect();
+    signature_of(name, &kinds)
+}
+
+fn signature_of(name: &str, kinds: &[ParamType]) -> String {
+    let kinds: Vec<String> = kinds.iter().map(|kind| kind.to_string()).collect();
+    format!("{}({})", name, kinds.join(","))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        analyzer::Upgradeability,
+        types::{ContractBuilder, ContractBytecode, ContractKind},
+    };
+    use semver::Version;
+    use std::fs;
+
+    fn load_test_abi(artifact_name: &str) -> Abi {
+        let content =
+            fs::read_to_string(format!("tests/artifacts/{}.json", artifact_name)).unwrap();
+        let artifact: serde_json::Value = serde_json::from_str(&content).unwrap();
+        serde_json::from_value(artifact["abi"].clone()).unwrap()
+    }
+
+    fn contract(name: &str, upgradeability: Upgradeability, signatures: &[&str]) -> Contract {
+        ContractBuilder::default()
+            .name(name.to_owned())
+            .kind(ContractKind::Contract(ContractBytecode::default()))
+            .version(Version::new(0, 8, 19))
+            .selectors(Some(SelectorTable {
+                selectors: signatures
+                    .iter()
+                    .map(|signature| Selector::new(SelectorKind::Function, signature.to_string()))
+                    .collect(),
+                collisions: vec![],
+            }))
+            .upgradeability(Some(upgradeability))
+            .build()
+            .unwrap()
+    }
+
+    fn with_pattern(pattern: ProxyPattern) -> Upgradeability {
+        Upgradeability {
+            pattern,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_selector_table_from_abi() {
+        let table = SelectorTable::from_abi(&load_test_abi("ERC20"));
+
+        assert_eq!(table.of_kind(SelectorKind::Function).count(), 12);
+        assert_eq!(
+            table
+                .find("0xa9059cbb")
+                .map(|selector| selector.signature.as_str()),
+            Some("transfer(address,uint256)")
+        );
+        assert_eq!(
+            table
+                .find("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+                .map(|selector| selector.signature.as_str()),
+            Some("Transfer(address,address,uint256)")
+        );
+    }
+
+    #[test]
+    fn test_selector_collisions() {
+        // both hash to 0x42966c68, in unrelated contracts so both compile
+        let proxy = contract(
+            "Proxy",
+            with_pattern(ProxyPattern::TransparentProxy),
+            &["collate_propagate_storage(bytes16)", "upgradeTo(address)"],
+        );
+        let token = contract(
+            "Token",
+            Upgradeability {
+                initializable: true,
+                ..Default::default()
+            },
+            &["burn(uint256)", "upgradeTo(address)"],
+        );
+        // not deployed behind the proxy
+        let helper = contract(
+            "Helper",
+            with_pattern(ProxyPattern::NonUpgradeable),
+            &["burn(uint256)"],
+        );
+        let contracts = vec![proxy, token, helper];
+
+        let collisions = SelectorResolver::collisions(&contracts[0], &contracts);
+
+        assert_eq!(
+            collisions,
+            vec![SelectorCollision {
+                kind: SelectorKind::Function,
+                selector: "0x42966c68".to_owned(),
+                declarations: vec![
+                    (
+                        "Proxy".to_owned(),
+                        "collate_propagate_storage(bytes16)".to_owned()
+                    ),
+                    ("Token".to_owned(), "burn(uint256)".to_owned()),
+                ],
+            }]
+        );
+        assert!(SelectorResolver::collisions(&contracts[1], &contracts).is_empty());
+        assert!(SelectorResolver::collisions(&contracts[2], &contracts).is_empty());
+    }
+
+    #[test]
+    fn test_diamond_facet_collisions() {
+        let diamond = contract(
+            "Diamond",
+            with_pattern(ProxyPattern::Diamond),
+            &["facets()"],
+        );
+        let ownership = contract(
+            "OwnershipFacet",
+            with_pattern(ProxyPattern::NonUpgradeable),
+            &["owner()", "transferOwnership(address)"],
+        );
+        let vault = contract(
+            "VaultFacet",
+            with_pattern(ProxyPattern::NonUpgradeable),
+            &["deposit(uint256)", "owner()"],
+        );
+        let contracts = vec![diamond, ownership, vault];
+
+        let collisions = SelectorResolver::collisions(&contracts[0], &contracts);
+
+        assert_eq!(
+            collisions,
+            vec![SelectorCollision {
+                kind: SelectorKind::Function,
+                selector: "0x8da5cb5b".to_owned(),
+                declarations: vec![
+                    ("OwnershipFacet".to_owned(), "owner()".to_owned()),
+                    ("VaultFacet".to_owned(), "owner()".to_owned()),
+                ],
+            }]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AppError,
+    types::{Contract, ContractOrigin},
+};
+
+use super::{parse_source, SourceContract, SourceFunction};
+
+/// Number of tokens in a shingle
+const SHINGLE_SIZE: usize = 4;
+
+/// A function body of a past contest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedFunction {
+    pub contest_uri: String,
+    pub contract: String,
+    /// Source file, relative to the project root
+    pub source_file: PathBuf,
+    pub key: String,
+    /// Same as [`SourceFunction::hash`]
+    pub hash: String,
+    /// Sorted hashes of the token shingles of the normalized body
+    pub shingles: Vec<u64>,
+}
+
+/// An indexed function similar to a queried one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarFunction {
+    /// Key of the queried function
+    pub query: String,
+    /// Jaccard similarity of the token shingles, `1.0` for identical normalized bodies
+    pub similarity: f64,
+    pub function: IndexedFunction,
+}
+
+/// Normalized function bodies of every processed contest, persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimilarityIndex {
+    pub functions: Vec<IndexedFunction>,
+}
+
+impl SimilarityIndex {
+    /// Functions with fewer tokens, such as getters, match everything and are not indexed
+    pub const MIN_TOKENS: usize = 16;
+    /// Matches below this similarity are not returned
+    pub const MIN_SIMILARITY: f64 = 0.6;
+
+    /// `similarity_index.json` in the workspace directory, next to the cloned contest repos
+    pub fn default_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("similarity_index.json")
+    }
+
+    /// Load an index, an empty index is returned if the file doesn't exist yet
+    pub fn load<P>(path: P) -> Result<Self, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<(), AppError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn contests(&self) -> BTreeSet<&str> {
+        self.functions
+            .iter()
+            .map(|function| function.contest_uri.as_str())
+            .collect()
+    }
+
+    /// Index the source contracts of a contest, replacing what was indexed for it before
+    pub fn add_contest(&mut self, contest_uri: &str, contracts: &[Contract]) {
+        self.remove_contest(contest_uri);
+
+        let mut seen: BTreeSet<(&Path, &str)> = BTreeSet::new();
+        for contract in contracts.iter().filter(|contract| {
+            contract.origin == ContractOrigin::Source
+                && seen.insert((&contract.source_file, &contract.name))
+        }) {
+            let Ok(source) = fs::read_to_string(contract.project_root.join(&contract.source_file))
+            else {
+                continue;
+            };
+            let Some(parsed) = parse_source(&source)
+                .into_iter()
+                .find(|parsed| parsed.name == contract.name)
+            else {
+                continue;
+            };
+            self.add_contract(contest_uri, &contract.source_file, &parsed);
+        }
+    }
+
+    pub fn add_contract(
+        &mut self,
+        contest_uri: &str,
+        source_file: &Path,
+        contract: &SourceContract,
+    ) {
+        for function in contract.functions.iter() {
+            let shingles = shingles(&function.normalized);
+            if shingles.is_empty() {
+                continue;
+            }
+            self.functions.push(IndexedFunction {
+                contest_uri: contest_uri.to_owned(),
+                contract: contract.name.clone(),
+                source_file: source_file.to_path_buf(),
+                key: function.key.clone(),
+                hash: function.hash.clone(),
+                shingles,
+            });
+        }
+    }
+
+    pub fn remove_contest(&mut self, contest_uri: &str) {
+        self.functions
+            .retain(|function| function.contest_uri != contest_uri);
+    }
+
+    /// Indexed functions similar to `function`, most similar first
+    pub fn query_function(&self, function: &SourceFunction) -> Vec<SimilarFunction> {
+        let shingles = shingles(&function.normalized);
+        if shingles.is_empty() {
+            return vec![];
+        }
+        let mut result: Vec<SimilarFunction> = self
+            .functions
+            .iter()
+            .filter_map(|indexed| {
+                let similarity = if indexed.hash == function.hash {
+                    1.0
+                } else {
+                    jaccard(&shingles, &indexed.shingles)
+                };
+                (similarity >= Self::MIN_SIMILARITY).then(|| SimilarFunction {
+                    query: function.key.clone(),
+                    similarity,
+                    function: indexed.clone(),
+                })
+            })
+            .collect();
+        result.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        result
+    }
+
+    /// Similar functions of every function of `contract`, excluding matches from `exclude_contest`,
+    /// usually the contest the contract belongs to
+    pub fn query_contract(
+        &self,
+        contract: &SourceContract,
+        exclude_contest: Option<&str>,
+    ) -> Vec<SimilarFunction> {
+        contract
+            .functions
+            .iter()
+            .flat_map(|function| self.query_function(function))
+            .filter(|similar| Some(similar.function.contest_uri.as_str()) != exclude_contest)
+            .collect()
+    }
+}
+
+/// Sorted, deduplicated hashes of every [`SHINGLE_SIZE`] consecutive tokens, empty if the code
+/// has fewer than [`SimilarityIndex::MIN_TOKENS`] tokens
+fn shingles(normalized: &str) -> Vec<u64> {
+    let tokens = tokenize(normalized);
+    if tokens.len() < SimilarityIndex::MIN_TOKENS {
+        return vec![];
+    }
+    let mut shingles: Vec<u64> = tokens
+        .windows(SHINGLE_SIZE)
+        .map(|window| fnv1a(window.join(" ").as_bytes()))
+        .collect();
+    shingles.sort_unstable();
+    shingles.dedup();
+    shingles
+}
+
+/// Identifiers and numbers as whole tokens, any other character as its own token
+fn tokenize(code: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = vec![];
+    let mut word_start: Option<usize> = None;
+    for (index, c) in code.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_' || c == '$';
+        match (is_word, word_start) {
+            (true, None) => word_start = Some(index),
+            (true, Some(_)) => {}
+            (false, start) => {
+                if let Some(start) = start {
+                    tokens.push(&code[start..index]);
+                    word_start = None;
+                }
+                if !c.is_whitespace() {
+                    tokens.push(&code[index..index + c.len_utf8()]);
+                }
+            }
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&code[start..]);
+    }
+    tokens
+}
+
+fn jaccard(a: &[u64], b: &[u64]) -> f64 {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let union = a.len() + b.len() - common;
+    if union == 0 {
+        0.0
+    } else {
+        common as f64 / union as f64
+    }
+}
+
+/// FNV-1a, stable across builds unlike the std hasher, so the index can be persisted
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PAST: &str = r#"
+contract Vault {
+    function withdraw(uint256 shares, address receiver) external returns (uint256 assets) {
+        assets = previewRedeem(shares);
+        _burn(msg.sender, shares);
+        asset.safeTransfer(receiver, assets);
+        emit Withdraw(msg.sender, receiver, assets, shares);
+    }
+
+    function totalAssets() public view returns (uint256) { return balance; }
+}
+"#;
+
+    const CURRENT: &str = r#"
+contract StakingVault {
+    function withdraw(uint256 shares, address to) external returns (uint256 assets) {
+        assets = previewRedeem(shares);
+        _burn(msg.sender, shares);
+        asset.safeTransfer(to, assets);
+        emit Withdraw(msg.sender, to, assets, shares);
+    }
+}
+"#;
+
+    #[test]
+    fn test_similarity_index() {
+        let mut index = SimilarityIndex::default();
+        let past = &parse_source(PAST)[0];
+        index.add_contract(
+            "https://code4rena.com/contests/2023-01-vault",
+            Path::new("src/Vault.sol"),
+            past,
+        );
+        // the getter is too short to be indexed
+        assert_eq!(index.functions.len(), 1);
+
+        let identical = index.query_function(&past.functions[0]);
+        assert_eq!(identical.len(), 1);
+        assert_eq!(identical[0].similarity, 1.0);
+
+        let current = &parse_source(CURRENT)[0];
+        let similar = index.query_contract(current, None);
+        assert_eq!(similar.len(), 1);
+        assert!(similar[0].similarity >= SimilarityIndex::MIN_SIMILARITY);
+        assert!(similar[0].similarity < 1.0);
+        assert_eq!(similar[0].function.contract, "Vault");
+        assert_eq!(
+            similar[0].function.contest_uri,
+            "https://code4rena.com/contests/2023-01-vault"
+        );
+        assert!(index
+            .query_contract(
+                current,
+                Some("https://code4rena.com/contests/2023-01-vault")
+            )
+            .is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.json");
+        index.save(&path).unwrap();
+        let loaded = SimilarityIndex::load(&path).unwrap();
+        assert_eq!(loaded.functions, index.functions);
+        assert!(SimilarityIndex::load(dir.path().join("missing.json"))
+            .unwrap()
+            .functions
+            .is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+use ethers::abi::{Abi, Param};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum TokenStandard {
+    Erc20,
+    Erc721,
+    Erc1155,
+    Erc4626,
+    /// ERC20 `permit`
+    Erc2612,
+    /// ERC3156 flash lender
+    Erc3156Lender,
+    /// ERC3156 flash borrower
+    Erc3156Borrower,
+    /// ERC777 `tokensReceived` hook
+    Erc777Recipient,
+    /// ERC777 `tokensToSend` hook
+    Erc777Sender,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Compliance {
+    /// Every function and event of the standard, with the expected return types
+    Full,
+    /// Some functions or events are missing
+    Partial,
+    /// A function of the standard returns something else than expected, e.g. an ERC20
+    /// `transfer` without `bool`
+    NonCompliant,
+}
+
+/// A standard implemented, at least partially, by a contract ABI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StandardMatch {
+    pub standard: TokenStandard,
+    pub compliance: Compliance,
+    /// Missing function and event signatures
+    pub missing: Vec<String>,
+    /// Functions whose return types differ from the standard
+    pub mismatched: Vec<String>,
+}
+
+struct StandardSpec {
+    standard: TokenStandard,
+    /// The ABI matches the standard if it has any of these functions
+    keys: &'static [&'static str],
+    /// Signatures and comma separated return types
+    functions: &'static [(&'static str, &'static str)],
+    events: &'static [&'static str],
+}
+
+const STANDARDS: &[StandardSpec] = &[
+    StandardSpec {
+        standard: TokenStandard::Erc20,
+        keys: &["transfer(address,uint256)"],
+        functions: &[
+            ("totalSupply()", "uint256"),
+            ("balanceOf(address)", "uint256"),
+            ("transfer(address,uint256)", "bool"),
+            ("transferFrom(address,address,uint256)", "bool"),
+            ("approve(address,uint256)", "bool"),
+            ("allowance(address,address)", "uint256"),
+        ],
+        events: &[
+            "Transfer(address,address,uint256)",
+            "Approval(address,address,uint256)",
+        ],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc721,
+        keys: &["ownerOf(uint256)"],
+        functions: &[
+            ("balanceOf(address)", "uint256"),
+            ("ownerOf(uint256)", "address"),
+            ("safeTransferFrom(address,address,uint256,bytes)", ""),
+            ("safeTransferFrom(address,address,uint256)", ""),
+            ("transferFrom(address,address,uint256)", ""),
+            ("approve(address,uint256)", ""),
+            ("setApprovalForAll(address,bool)", ""),
+            ("getApproved(uint256)", "address"),
+            ("isApprovedForAll(address,address)", "bool"),
+        ],
+        events: &[
+            "Transfer(address,address,uint256)",
+            "Approval(address,address,uint256)",
+            "ApprovalForAll(address,address,bool)",
+        ],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc1155,
+        keys: &[
+            "balanceOfBatch(address[],uint256[])",
+            "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+        ],
+        functions: &[
+            (
+                "safeTransferFrom(address,address,uint256,uint256,bytes)",
+                "",
+            ),
+            (
+                "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+                "",
+            ),
+            ("balanceOf(address,uint256)", "uint256"),
+            ("balanceOfBatch(address[],uint256[])", "uint256[]"),
+            ("setApprovalForAll(address,bool)", ""),
+            ("isApprovedForAll(address,address)", "bool"),
+        ],
+        events: &[
+            "TransferSingle(address,address,address,uint256,uint256)",
+            "TransferBatch(address,address,address,uint256[],uint256[])",
+            "ApprovalForAll(address,address,bool)",
+            "URI(string,uint256)",
+        ],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc4626,
+        keys: &["convertToShares(uint256)", "convertToAssets(uint256)"],
+        functions: &[
+            ("asset()", "address"),
+            ("totalAssets()", "uint256"),
+            ("convertToShares(uint256)", "uint256"),
+            ("convertToAssets(uint256)", "uint256"),
+            ("maxDeposit(address)", "uint256"),
+            ("previewDeposit(uint256)", "uint256"),
+            ("deposit(uint256,address)", "uint256"),
+            ("maxMint(address)", "uint256"),
+            ("previewMint(uint256)", "uint256"),
+            ("mint(uint256,address)", "uint256"),
+            ("maxWithdraw(address)", "uint256"),
+            ("previewWithdraw(uint256)", "uint256"),
+            ("withdraw(uint256,address,address)", "uint256"),
+            ("maxRedeem(address)", "uint256"),
+            ("previewRedeem(uint256)", "uint256"),
+            ("redeem(uint256,address,address)", "uint256"),
+        ],
+        events: &[
+            "Deposit(address,address,uint256,uint256)",
+            "Withdraw(address,address,address,uint256,uint256)",
+        ],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc2612,
+        keys: &["permit(address,address,uint256,uint256,uint8,bytes32,bytes32)"],
+        functions: &[
+            (
+                "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+                "",
+            ),
+            ("nonces(address)", "uint256"),
+            ("DOMAIN_SEPARATOR()", "bytes32"),
+        ],
+        events: &[],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc3156Lender,
+        keys: &["flashLoan(address,address,uint256,bytes)"],
+        functions: &[
+            ("maxFlashLoan(address)", "uint256"),
+            ("flashFee(address,uint256)", "uint256"),
+            ("flashLoan(address,address,uint256,bytes)", "bool"),
+        ],
+        events: &[],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc3156Borrower,
+        keys: &["onFlashLoan(address,address,uint256,uint256,bytes)"],
+        functions: &[(
+            "onFlashLoan(address,address,uint256,uint256,bytes)",
+            "bytes32",
+        )],
+        events: &[],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc777Recipient,
+        keys: &["tokensReceived(address,address,address,uint256,bytes,bytes)"],
+        functions: &[(
+            "tokensReceived(address,address,address,uint256,bytes,bytes)",
+            "",
+        )],
+        events: &[],
+    },
+    StandardSpec {
+        standard: TokenStandard::Erc777Sender,
+        keys: &["tokensToSend(address,address,address,uint256,bytes,bytes)"],
+        functions: &[(
+            "tokensToSend(address,address,address,uint256,bytes,bytes)",
+            "",
+        )],
+        events: &[],
+    },
+];
+
+/// Detect the standards implemented by an ABI
+pub struct StandardResolver {}
+
+impl StandardResolver {
+    pub fn detect(abi: &Abi) -> Vec<StandardMatch> {
+        let functions: Vec<(String, String)> = abi
+            .functions()
+            .map(|function| {
+                (
+                    signature(&function.name, &function.inputs),
+                    kinds(&function.outputs),
+                )
+            })
+            .collect();
+        let events: Vec<String> = abi
+            .events()
+            .map(|event| {
+                let inputs: Vec<String> = event
+                    .inputs
+                    .iter()
+                    .map(|input| input.kind.to_string())
+                    .collect();
+                format!("{}({})", event.name, inputs.join(","))
+            })
+            .collect();
+        let find_function = |wanted: &str| {
+            functions
+                .iter()
+                .find(|(signature, _)| signature == wanted)
+                .map(|(_, outputs)| outputs.as_str())
+        };
+
+        STANDARDS
+            .iter()
+            .filter(|spec| spec.keys.iter().any(|key| find_function(key).is_some()))
+            .map(|spec| {
+                let mut missing: Vec<String> = vec![];
+                let mut mismatched: Vec<String> = vec![];
+                for (signature, outputs) in spec.functions.iter() {
+                    match find_function(signature) {
+                        None => missing.push(signature.to_string()),
+                        Some(actual) if actual != *outputs => {
+                            mismatched.push(signature.to_string())
+                        }
+                        Some(_) => {}
+                    }
+                }
+                for event in spec.events.iter() {
+                    if !events.iter().any(|actual| actual == event) {
+                        missing.push(event.to_string());
+                    }
+                }
+
+                let compliance = if !mismatched.is_empty() {
+                    Compliance::NonCompliant
+                } else if !missing.is_empty() {
+                    Compliance::Partial
+                } else {
+                    Compliance::Full
+                };
+                StandardMatch {
+                    standard: spec.standard,
+                    compliance,
+                    missing,
+                    mismatched,
+                }
+            })
+            .collect()
+    }
+}
+
+fn signature(name: &str, inputs: &[Param]) -> String {
+    format!("{}({})", name, kinds(inputs))
+}
+
+fn kinds(params: &[Param]) -> String {
+    params
+        .iter()
+        .map(|param| param.kind.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn function(name: &str, inputs: &[&str], outputs: &[&str]) -> serde_json::Value {
+        let params = |types: &[&str]| -> Vec<serde_json::Value> {
+            types
+                .iter()
+                .map(|kind| json!({ "name": "", "type": kind, "internalType": kind }))
+                .collect()
+        };
+        json!({
+            "type": "function", "name": name, "stateMutability": "nonpayable",
+            "inputs": params(inputs), "outputs": params(outputs),
+        })
+    }
+
+    #[test]
+    fn test_detect_erc20_and_permit() {
+        let content = fs::read_to_string("tests/artifacts/ERC20.json").unwrap();
+        let artifact: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let abi: Abi = serde_json::from_value(artifact["abi"].clone()).unwrap();
+
+        let standards = StandardResolver::detect(&abi);
+
+        assert_eq!(
+            standards
+                .iter()
+                .map(|item| (item.standard, item.compliance))
+                .collect::<Vec<_>>(),
+            vec![
+                (TokenStandard::Erc20, Compliance::Full),
+                (TokenStandard::Erc2612, Compliance::Full),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_non_compliant_and_partial() {
+        // USDT style `transfer` without return value, and a vault without previews
+        let abi: Abi = serde_json::from_value(json!([
+            function("transfer", &["address", "uint256"], &[]),
+            function("balanceOf", &["address"], &["uint256"]),
+            function("asset", &[], &["address"]),
+            function("convertToShares", &["uint256"], &["uint256"]),
+            function("deposit", &["uint256", "address"], &["uint256"]),
+        ]))
+        .unwrap();
+
+        let standards = StandardResolver::detect(&abi);
+
+        assert_eq!(standards[0].standard, TokenStandard::Erc20);
+        assert_eq!(standards[0].compliance, Compliance::NonCompliant);
+        assert_eq!(standards[0].mismatched, vec!["transfer(address,uint256)"]);
+        assert_eq!(standards[1].standard, TokenStandard::Erc4626);
+        assert_eq!(standards[1].compliance, Compliance::Partial);
+        assert!(standards[1]
+            .missing
+            .contains(&"previewDeposit(uint256)".to_owned()));
+    }
+
+    #[test]
+    fn test_detect_erc1155_only() {
+        // shares `setApprovalForAll` with ERC721 and `asset()` with ERC4626
+        let abi: Abi = serde_json::from_value(json!([
+            function("balanceOf", &["address", "uint256"], &["uint256"]),
+            function(
+                "balanceOfBatch",
+                &["address[]", "uint256[]"],
+                &["uint256[]"]
+            ),
+            function("setApprovalForAll", &["address", "bool"], &[]),
+            function("isApprovedForAll", &["address", "address"], &["bool"]),
+            function("asset", &[], &["address"]),
+        ]))
+        .unwrap();
+
+        let standards: Vec<TokenStandard> = StandardResolver::detect(&abi)
+            .iter()
+            .map(|item| item.standard)
+            .collect();
+
+        assert_eq!(standards, vec![TokenStandard::Erc1155]);
+    }
+}
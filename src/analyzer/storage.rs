@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+
+use ethers::types::U256;
+use ethers_solc::artifacts::StorageLayout as SolcStorageLayout;
+use serde::Serialize;
+
+use crate::types::Contract;
+
+/// A state variable as laid out in storage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageVariable {
+    pub name: String,
+    /// Contract declaring the variable
+    pub contract: String,
+    pub slot: U256,
+    /// Byte offset inside the slot
+    pub offset: u64,
+    /// Type label, e.g. `mapping(address => uint256)`
+    pub type_label: String,
+    pub number_of_bytes: u64,
+}
+
+/// Storage layout of a contract, from the solc `storageLayout` output, in slot order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StorageLayout {
+    pub variables: Vec<StorageVariable>,
+}
+
+impl StorageLayout {
+    pub fn from_solc(layout: &SolcStorageLayout) -> Self {
+        let mut variables: Vec<StorageVariable> = layout
+            .storage
+            .iter()
+            .map(|storage| {
+                let storage_type = layout.types.get(&storage.storage_type);
+                StorageVariable {
+                    name: storage.label.clone(),
+                    // solc uses `path/File.sol:Name`
+                    contract: storage
+                        .contract
+                        .rsplit(':')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    slot: U256::from_dec_str(&storage.slot).unwrap_or_default(),
+                    offset: storage.offset.max(0) as u64,
+                    type_label: storage_type
+                        .map(|storage_type| storage_type.label.clone())
+                        .unwrap_or_else(|| storage.storage_type.clone()),
+                    number_of_bytes: storage_type
+                        .and_then(|storage_type| storage_type.number_of_bytes.parse().ok())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        variables.sort_by_key(|variable| (variable.slot, variable.offset));
+
+        Self { variables }
+    }
+
+    pub fn find(&self, contract: &str, name: &str) -> Option<&StorageVariable> {
+        self.variables
+            .iter()
+            .find(|variable| variable.contract == contract && variable.name == name)
+    }
+
+    /// Variables declared by `contract`
+    pub fn declared_by<'a>(
+        &'a self,
+        contract: &'a str,
+    ) -> impl Iterator<Item = &'a StorageVariable> {
+        self.variables
+            .iter()
+            .filter(move |variable| variable.contract == contract)
+    }
+}
+
+/// Change of a state variable between two versions of a storage layout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum StorageChange {
+    /// The variable is gone, its slot is left for the next variables to reuse
+    Removed(StorageVariable),
+    /// The variable is stored at a different slot or offset
+    Moved {
+        old: StorageVariable,
+        new: StorageVariable,
+    },
+    /// Same location, different type
+    TypeChanged {
+        old: StorageVariable,
+        new: StorageVariable,
+    },
+    /// A new variable is stored where the old layout already had data
+    Inserted(StorageVariable),
+    /// A new variable is stored after the old layout, which is safe
+    Appended(StorageVariable),
+}
+
+impl StorageChange {
+    /// Whether the change corrupts the storage of an already deployed proxy
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, StorageChange::Appended(_))
+    }
+}
+
+/// A base of an upgradeable contract declaring state variables without a `__gap` array
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingStorageGap {
+    pub contract: String,
+    pub base: String,
+}
+
+/// Storage layout checks for upgradeable contracts
+pub struct StorageResolver {}
+
+impl StorageResolver {
+    /// Whether the contract inherits from an OpenZeppelin style `Initializable` or
+    /// `*Upgradeable` base
+    pub fn is_upgradeable(contract: &Contract) -> bool {
+        contract.inheritance.as_ref().is_some_and(|inheritance| {
+            inheritance
+                .linearized
+                .iter()
+                .skip(1)
+                .any(|base| base == "Initializable" || base.ends_with("Upgradeable"))
+        })
+    }
+
+    /// Bases of an upgradeable contract which declare state variables but no `__gap`, so that
+    /// adding a variable to them in an upgrade shifts the storage of every derived contract.
+    /// `Initializable` is skipped, its two flags are meant to stay the first slot.
+    pub fn missing_gaps(contract: &Contract) -> Vec<MissingStorageGap> {
+        let (Some(layout), Some(inheritance)) = (&contract.storage_layout, &contract.inheritance)
+        else {
+            return vec![];
+        };
+        if !Self::is_upgradeable(contract) {
+            return vec![];
+        }
+
+        inheritance
+            .linearized
+            .iter()
+            .skip(1)
+            .filter(|base| base.as_str() != "Initializable")
+            .filter(|base| {
+                let mut variables = layout.declared_by(base).peekable();
+                variables.peek().is_some() && !variables.any(|variable| variable.name == "__gap")
+            })
+            .map(|base| MissingStorageGap {
+                contract: contract.name.clone(),
+                base: base.clone(),
+            })
+            .collect()
+    }
+
+    /// Changes from the `old` to the `new` layout of a contract. Variables are matched by
+    /// declaring contract and name; `__gap` arrays are expected to shrink and are ignored.
+    pub fn diff(old: &StorageLayout, new: &StorageLayout) -> Vec<StorageChange> {
+        let is_gap = |variable: &&StorageVariable| variable.name == "__gap";
+        let mut changes: Vec<StorageChange> = vec![];
+
+        for old_variable in old.variables.iter().filter(|v| !is_gap(v)) {
+            match new.find(&old_variable.contract, &old_variable.name) {
+                None => changes.push(StorageChange::Removed(old_variable.clone())),
+                Some(new_variable)
+                    if (new_variable.slot, new_variable.offset)
+                        != (old_variable.slot, old_variable.offset) =>
+                {
+                    changes.push(StorageChange::Moved {
+                        old: old_variable.clone(),
+                        new: new_variable.clone(),
+                    })
+                }
+                Some(new_variable) if new_variable.type_label != old_variable.type_label => changes
+                    .push(StorageChange::TypeChanged {
+                        old: old_variable.clone(),
+                        new: new_variable.clone(),
+                    }),
+                Some(_) => {}
+            }
+        }
+
+        // the first slot after the old layout, gaps included
+        let old_end = old
+            .variables
+            .iter()
+            .map(|variable| variable.slot + slot_count(variable))
+            .max()
+            .unwrap_or_default();
+        for new_variable in new.variables.iter().filter(|v| !is_gap(v)) {
+            if old
+                .find(&new_variable.contract, &new_variable.name)
+                .is_some()
+            {
+                continue;
+            }
+            let overlaps_gap = old.variables.iter().filter(is_gap).any(|gap| {
+                new_variable.slot >= gap.slot && new_variable.slot < gap.slot + slot_count(gap)
+            });
+            if new_variable.slot >= old_end || overlaps_gap {
+                changes.push(StorageChange::Appended(new_variable.clone()));
+            } else {
+                changes.push(StorageChange::Inserted(new_variable.clone()));
+            }
+        }
+
+        changes
+    }
+
+    /// Layout changes of every contract found in both `old` and `new`, e.g. before and after a
+    /// repo pull. Contracts are matched by project root, source file and name.
+    pub fn diff_contracts(
+        old: &[Contract],
+        new: &[Contract],
+    ) -> BTreeMap<String, Vec<StorageChange>> {
+        let mut result = BTreeMap::new();
+        for new_contract in new.iter() {
+            let old_contract = old.iter().find(|old_contract| {
+                old_contract.project_root == new_contract.project_root
+                    && old_contract.source_file == new_contract.source_file
+                    && old_contract.name == new_contract.name
+            });
+            let (Some(old_layout), Some(new_layout)) = (
+                old_contract.and_then(|contract| contract.storage_layout.as_ref()),
+                new_contract.storage_layout.as_ref(),
+            ) else {
+                continue;
+            };
+
+            let changes = Self::diff(old_layout, new_layout);
+            if !changes.is_empty() {
+                result.insert(new_contract.name.clone(), changes);
+            }
+        }
+        result
+    }
+}
+
+fn slot_count(variable: &StorageVariable) -> U256 {
+    U256::from(variable.number_of_bytes.max(1)).saturating_add(U256::from(31)) / 32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        analyzer::Inheritance,
+        types::{ContractBuilder, ContractKind},
+    };
+    use semver::Version;
+    use serde_json::json;
+
+    fn layout(variables: &[(&str, &str, u64, &str)]) -> StorageLayout {
+        let solc_layout: SolcStorageLayout = serde_json::from_value(json!({
+            "storage": variables.iter().map(|(contract, name, slot, type_id)| json!({
+                "astId": 1,
+                "contract": format!("src/{contract}.sol:{contract}"),
+                "label": name,
+                "offset": 0,
+                "slot": slot.to_string(),
+                "type": type_id,
+            })).collect::<Vec<_>>(),
+            "types": {
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_uint8": { "encoding": "inplace", "label": "uint8", "numberOfBytes": "1" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_array(t_uint256)48_storage": {
+                    "encoding": "inplace", "label": "uint256[48]", "numberOfBytes": "1536"
+                }
+            }
+        }))
+        .unwrap();
+        StorageLayout::from_solc(&solc_layout)
+    }
+
+    #[test]
+    fn test_storage_diff() {
+        let old = layout(&[
+            ("Vault", "owner", 0, "t_address"),
+            ("Vault", "totalAssets", 1, "t_uint256"),
+            ("Vault", "fee", 2, "t_uint256"),
+        ]);
+        let new = layout(&[
+            ("Vault", "owner", 0, "t_address"),
+            ("Vault", "paused", 1, "t_uint256"),
+            ("Vault", "totalAssets", 2, "t_uint256"),
+            ("Vault", "fee", 3, "t_address"),
+            ("Vault", "cap", 4, "t_uint256"),
+        ]);
+
+        let changes = StorageResolver::diff(&old, &new);
+
+        assert_eq!(changes.len(), 4);
+        assert!(
+            matches!(&changes[0], StorageChange::Moved { new, .. } if new.name == "totalAssets")
+        );
+        assert!(matches!(&changes[1], StorageChange::Moved { new, .. } if new.name == "fee"));
+        assert!(matches!(&changes[2], StorageChange::Inserted(v) if v.name == "paused"));
+        assert!(matches!(&changes[3], StorageChange::Appended(v) if v.name == "cap"));
+        assert!(!changes[3].is_breaking());
+    }
+
+    #[test]
+    fn test_storage_diff_uses_gap() {
+        let old = layout(&[
+            ("Base", "owner", 0, "t_address"),
+            ("Base", "__gap", 1, "t_array(t_uint256)48_storage"),
+            ("Vault", "totalAssets", 49, "t_uint256"),
+        ]);
+        let new = layout(&[
+            ("Base", "owner", 0, "t_address"),
+            ("Base", "admin", 1, "t_address"),
+            ("Base", "__gap", 2, "t_array(t_uint256)48_storage"),
+            ("Vault", "totalAssets", 49, "t_uint256"),
+        ]);
+
+        let changes = StorageResolver::diff(&old, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], StorageChange::Appended(v) if v.name == "admin"));
+    }
+
+    #[test]
+    fn test_missing_gaps() {
+        // OpenZeppelin 4.x `Initializable` packs its flags in slot 0 and has no gap
+        let mut storage_layout = layout(&[
+            ("Initializable", "_initialized", 0, "t_uint8"),
+            ("Initializable", "_initializing", 0, "t_bool"),
+            ("OwnableUpgradeable", "_owner", 1, "t_address"),
+            (
+                "OwnableUpgradeable",
+                "__gap",
+                2,
+                "t_array(t_uint256)48_storage",
+            ),
+            ("Fees", "fee", 50, "t_uint256"),
+            ("Vault", "totalAssets", 51, "t_uint256"),
+        ]);
+        storage_layout.variables[1].offset = 1;
+        assert_eq!(storage_layout.declared_by("Initializable").count(), 2);
+
+        let contract = ContractBuilder::default()
+            .name("Vault".to_owned())
+            .kind(ContractKind::Interface)
+            .version(Version::new(0, 8, 19))
+            .inheritance(Some(Inheritance {
+                linearized: vec![
+                    "Vault".to_owned(),
+                    "Fees".to_owned(),
+                    "OwnableUpgradeable".to_owned(),
+                    "Initializable".to_owned(),
+                ],
+                ..Default::default()
+            }))
+            .storage_layout(Some(storage_layout))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            StorageResolver::missing_gaps(&contract),
+            vec![MissingStorageGap {
+                contract: "Vault".to_owned(),
+                base: "Fees".to_owned(),
+            }]
+        );
+    }
+}
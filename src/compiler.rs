@@ -11,8 +11,13 @@ use std::{
 };
 
 use crate::{
+    analyzer::{FileMetrics, ForkMatch, ForkResolver, UpstreamCorpus},
     errors::AppError,
-    types::{Contest, Contract, ContractBytecode, ContractKind, FoundryConfig, RepoUri},
+    graph::ImportGraph,
+    types::{
+        AdvisoryMatch, Contest, Contract, ContractBytecode, ContractKind, ContractOrigin,
+        FoundryConfig, Library, RepoUri,
+    },
 };
 use ethers_solc::{
     buildinfo::BuildInfo, output::ProjectCompileOutput, project_util::TempProject,
@@ -23,19 +28,42 @@ use walkdir::WalkDir;
 
 mod contract;
 pub use contract::*;
+mod library;
+pub use library::*;
 mod project;
 pub use project::*;
+mod project_root;
+pub use project_root::*;
+mod scope;
+pub use scope::*;
 
 /// Clone the repo if it's not cloned, else pull from branch main
-pub fn clone_or_pull_repo(repo_uri: &str) -> Result<PathBuf, AppError> {
+pub fn clone_or_pull_repo<P>(workspace_dir: P, repo_uri: &str) -> Result<PathBuf, AppError>
+where
+    P: AsRef<Path>,
+{
     // create directory contains the contest repo
-    let repo_dir = project_dir_from_uri(repo_uri);
+    let repo_dir = project_dir_from_uri(workspace_dir, repo_uri)?;
 
     info!("Creating directory if not existed: {:?}", repo_dir);
     fs::create_dir_all(repo_dir.clone()).map_err(|e| AppError::UnknownError(anyhow!(e)))?;
 
-    // TODO: pull the repo if the repo is existed, for now we just clear the repo and reclone
-    if is_directory_empty(&repo_dir) {
+    if !is_directory_empty(&repo_dir) {
+        // keep the current checkout if the pull fails, e.g. on a force-pushed branch
+        let output = Command::new("git")
+            .args(&["pull", "--ff-only"])
+            .current_dir(&repo_dir)
+            .output()?;
+        if output.status.success() {
+            info!("Repository {:?} pulled successfully!", repo_uri);
+        } else {
+            error!(
+                "Error pulling repository {:?}, error: {}",
+                repo_uri,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    } else {
         // Execute the `git clone` command
         let output = Command::new("git")
             .args(&["clone", &repo_uri, repo_dir.as_os_str().to_str().unwrap()])
@@ -60,19 +88,193 @@ pub fn clone_or_pull_repo(repo_uri: &str) -> Result<PathBuf, AppError> {
     Ok(repo_dir)
 }
 
+/// Commit checked out in a cloned repo
+pub fn repo_commit<P>(repo_dir: P) -> Option<String>
+where
+    P: AsRef<Path>,
+{
+    let output = Command::new("git")
+        .args(&["rev-parse", "HEAD"])
+        .current_dir(repo_dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Commit of the default branch of a remote repo, without cloning it
+pub fn remote_commit(repo_uri: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(&["ls-remote", repo_uri, "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(|commit| commit.to_owned())
+}
+
+/// Contracts found in a repo, along with the failures of the project roots that were skipped
+#[derive(Debug, Clone, Default)]
+pub struct ContractsReport {
+    pub contracts: Vec<Contract>,
+    /// Import graph of each compiled project root
+    pub import_graphs: Vec<ImportGraph>,
+    /// Metrics of every source file, by project root
+    pub file_metrics: Vec<(PathBuf, Vec<FileMetrics>)>,
+    /// Third-party libraries of every project root
+    pub libraries: Vec<Library>,
+    /// Bundled advisories affecting [`Self::libraries`]
+    pub advisories: Vec<AdvisoryMatch>,
+    /// Contracts forked from the upstream corpus, see [`FindContractsOptions::upstream_corpus`]
+    pub forks: Vec<ForkMatch>,
+    pub diagnostics: Vec<ProjectDiagnostic>,
+}
+
+impl ContractsReport {
+    /// Import graph of the project the contract belongs to
+    pub fn import_graph_of(&self, contract: &Contract) -> Option<&ImportGraph> {
+        self.import_graphs
+            .iter()
+            .find(|graph| graph.project_root == contract.project_root)
+    }
+}
+
+/// Options used by [`find_all_contracts_with_options`]
+#[derive(Debug, Clone)]
+pub struct FindContractsOptions {
+    pub project_roots: ProjectRootOptions,
+    /// Only keep contracts declared in the contest scope
+    pub only_in_scope: bool,
+    /// Only keep contracts with these origins, keep all if empty. Only the project's own
+    /// sources by default
+    pub origins: Vec<ContractOrigin>,
+    /// Directory of upstream protocol sources to detect forks against
+    pub upstream_corpus: Option<PathBuf>,
+    /// Advisories to match libraries against, instead of the bundled list
+    pub advisories_file: Option<PathBuf>,
+}
+
+impl Default for FindContractsOptions {
+    fn default() -> Self {
+        Self {
+            project_roots: Default::default(),
+            only_in_scope: false,
+            origins: vec![ContractOrigin::Source],
+            upstream_corpus: None,
+            advisories_file: None,
+        }
+    }
+}
+
+impl FindContractsOptions {
+    /// Options keeping tests, scripts, mocks and libs along with the project's own sources
+    pub fn all_origins() -> Self {
+        Self {
+            origins: vec![],
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, contract: &Contract) -> bool {
+        (!self.only_in_scope || contract.in_scope)
+            && (self.origins.is_empty() || self.origins.contains(&contract.origin))
+    }
+}
+
+pub fn find_all_contracts<P>(repo_dir: P) -> Result<ContractsReport, AppError>
+where
+    P: AsRef<Path>,
+{
+    find_all_contracts_with_options(repo_dir, &FindContractsOptions::default())
+}
+
 #[instrument(skip_all)]
-pub fn find_all_contracts<P>(repo_dir: P) -> Result<Vec<Contract>, AppError>
+pub fn find_all_contracts_with_options<P>(
+    repo_dir: P,
+    options: &FindContractsOptions,
+) -> Result<ContractsReport, AppError>
 where
     P: AsRef<Path>,
 {
-    let projects = ProjectResolver::parse(repo_dir)?;
-
-    let contracts = projects
-        .iter()
-        .filter_map(|project| ContractResolver::get_contracts_from_project(&project).ok())
-        .flatten()
-        .collect::<Vec<Contract>>();
-    Ok(contracts)
+    let scope = ScopeResolver::parse(repo_dir.as_ref());
+    let reports = ProjectResolver::parse_with_options(repo_dir, &options.project_roots)?;
+    let corpus = options
+        .upstream_corpus
+        .as_ref()
+        .map(UpstreamCorpus::load)
+        .transpose()?;
+
+    let mut result = ContractsReport::default();
+    for report in reports {
+        if let Some(failure) = report.failure {
+            result.diagnostics.push(failure);
+            continue;
+        }
+        let Some(project) = report.project else {
+            continue;
+        };
+
+        match ContractResolver::get_import_graph_from_project(&project) {
+            Ok(graph) => {
+                let files: Vec<PathBuf> = graph.files().cloned().collect();
+                result
+                    .libraries
+                    .extend(LibraryResolver::find_libraries(project.root(), &files));
+                result.import_graphs.push(graph);
+            }
+            Err(e) => result.diagnostics.push(ProjectDiagnostic::new(
+                &report.root.path,
+                ProjectStage::ExtractContracts,
+                &e,
+            )),
+        }
+
+        match ContractResolver::get_file_metrics_from_project(&project) {
+            Ok(file_metrics) => result
+                .file_metrics
+                .push((project.root().clone(), file_metrics)),
+            Err(e) => result.diagnostics.push(ProjectDiagnostic::new(
+                &report.root.path,
+                ProjectStage::ExtractContracts,
+                &e,
+            )),
+        }
+
+        match ContractResolver::get_contracts_from_project(&project, &scope) {
+            Ok(contracts) => {
+                let contracts: Vec<Contract> = contracts
+                    .into_iter()
+                    .filter(|contract| options.matches(contract))
+                    .collect();
+                if let Some(corpus) = corpus.as_ref() {
+                    result
+                        .forks
+                        .extend(ForkResolver::detect_in_project(&contracts, corpus));
+                }
+                result.contracts.extend(contracts);
+            }
+            Err(e) => result.diagnostics.push(ProjectDiagnostic::new(
+                &report.root.path,
+                ProjectStage::ExtractContracts,
+                &e,
+            )),
+        }
+    }
+    let advisories = match options.advisories_file.as_ref() {
+        Some(path) => AdvisoryDatabase::load(path).unwrap_or_else(|e| {
+            error!("Load advisories {path:?} error {e:?}, using the bundled list");
+            AdvisoryDatabase::bundled()
+        }),
+        None => AdvisoryDatabase::bundled(),
+    };
+    result.advisories = advisories.matches(&result.libraries);
+    Ok(result)
 }
 
 fn parse_remappings<P>(file_path: P) -> Result<Vec<Remapping>, AppError>
@@ -134,9 +336,9 @@ mod test {
     fn test_find_all_contracts() {
         let repo_dir = PathBuf::from("contests/2023-05-maia");
 
-        let contracts = find_all_contracts(&repo_dir).unwrap();
+        let report = find_all_contracts(&repo_dir).unwrap();
 
-        assert_gt!(contracts.len(), 0);
+        assert_gt!(report.contracts.len(), 0);
         // println!("contracts {:#?}", contracts);
     }
 }
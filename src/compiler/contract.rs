@@ -1,18 +1,35 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use ethers::{etherscan::contract::ContractMetadata, types::Bytes};
+use ethers::{
+    etherscan::contract::ContractMetadata,
+    types::Bytes,
+    utils::{hex, keccak256},
+};
 use ethers_solc::{
     artifacts::{Ast, BytecodeObject, NodeType},
-    cache::SolFilesCache,
+    cache::{CacheEntry, SolFilesCache},
     Artifact, ArtifactOutput, ConfigurableArtifacts, Project,
 };
 use rayon::prelude::*;
 use rr_logging::info;
 use semver::Version;
 
+use super::LibraryResolver;
 use crate::{
+    analyzer::{
+        sort_by_reading_order, AccessResolver, FileMetrics, InheritanceResolver, MetricsResolver,
+        ProxyResolver, SelectorCollision, SelectorResolver, SelectorTable, SourceAsts,
+        StandardResolver, StorageLayout,
+    },
     errors::AppError,
-    types::{Contract, ContractBytecode, ContractFromArtifact, ContractKind},
+    graph::ImportGraph,
+    types::{
+        Contract, ContractBytecode, ContractFromArtifact, ContractKind, ContractOrigin,
+        ContractType, Scope,
+    },
 };
 
 pub struct ContractResolver {}
@@ -100,8 +117,11 @@ impl ContractResolver {
         })
     }
 
-    /// Get all contracts from project
-    pub fn get_contracts_from_project<T>(project: &Project<T>) -> Result<Vec<Contract>, AppError>
+    /// Get all contracts from project, tagging the ones declared in `scope`
+    pub fn get_contracts_from_project<T>(
+        project: &Project<T>,
+        scope: &Scope,
+    ) -> Result<Vec<Contract>, AppError>
     where
         T: ArtifactOutput,
     {
@@ -112,6 +132,7 @@ impl ContractResolver {
             e
         })?;
         let project_root = project.root();
+        let project_paths = &project.paths;
         let source_files: Vec<PathBuf> = solc_cache
             .files
             .iter()
@@ -123,11 +144,25 @@ impl ContractResolver {
             .par_iter()
             .filter_map(|file| {
                 // info!("Handling file {:?}", file);
-                let contracts = Self::get_contracts_from_cache(
+                let mut contracts = Self::get_contracts_from_cache(
                     solc_cache.clone(),
                     project_root.clone(),
                     file.clone(),
                 );
+                let origin = ContractOrigin::classify(file, project_paths);
+                let library =
+                    LibraryResolver::library_of(file.strip_prefix(project_root).unwrap_or(file))
+                        .map(|(name, _)| name.to_owned());
+                for contract in contracts.iter_mut() {
+                    contract.origin = origin;
+                    contract.library = library.clone();
+                }
+                if let Some(scope_entry) = scope.find(project_root.join(file)) {
+                    for contract in contracts.iter_mut() {
+                        contract.in_scope = true;
+                        contract.declared_nsloc = scope_entry.nsloc;
+                    }
+                }
 
                 if contracts.len() > 0 {
                     Some(contracts)
@@ -137,13 +172,90 @@ impl ContractResolver {
             })
             .flatten()
             .collect::<Vec<Contract>>();
-        contracts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let asts = SourceAsts::from_cache(&solc_cache, &project_root);
+        let inheritance_resolver = InheritanceResolver::new(&asts);
+        for contract in contracts.iter_mut() {
+            let Some(definition) = asts.contract_definition(&contract.source_file, &contract.name)
+            else {
+                continue;
+            };
+            contract.contract_type = ContractType::from_definition(definition);
+            contract.inheritance =
+                inheritance_resolver.inheritance_of(asts.job(&contract.source_file), definition);
+
+            let source = fs::read_to_string(project_root.join(&contract.source_file)).ok();
+            contract.metrics = Some(MetricsResolver::contract_metrics(
+                definition,
+                source.as_deref(),
+            ));
+        }
+
+        let proxy_resolver = ProxyResolver::new(&asts);
+        let access_resolver = AccessResolver::new(&asts);
+        for contract in contracts.iter_mut() {
+            contract.upgradeability = Some(proxy_resolver.upgradeability_of(contract));
+            contract.call_surface = Some(access_resolver.surface_of(contract));
+        }
+
+        // proxies and diamonds are known once upgradeability is resolved
+        let collisions: Vec<Vec<SelectorCollision>> = contracts
+            .iter()
+            .map(|contract| SelectorResolver::collisions(contract, &contracts))
+            .collect();
+        for (contract, collisions) in contracts.iter_mut().zip(collisions) {
+            if let Some(selectors) = contract.selectors.as_mut() {
+                selectors.collisions = collisions;
+            }
+        }
+
+        let import_graph = ImportGraph::from_cache(&solc_cache, &project_root);
+        let contracts = sort_by_reading_order(contracts, &[import_graph]);
 
         info!("Number of contracts = {:?}", contracts.len());
 
         Ok(contracts)
     }
 
+    /// Build the import graph of every file in the project's solc cache
+    pub fn get_import_graph_from_project<T>(project: &Project<T>) -> Result<ImportGraph, AppError>
+    where
+        T: ArtifactOutput,
+    {
+        let solc_cache = project.read_cache_file()?;
+        Ok(ImportGraph::from_cache(&solc_cache, project.root()))
+    }
+
+    /// Metrics of every source file of the project
+    pub fn get_file_metrics_from_project<T>(
+        project: &Project<T>,
+    ) -> Result<Vec<FileMetrics>, AppError>
+    where
+        T: ArtifactOutput,
+    {
+        let solc_cache = project.read_cache_file()?;
+        let project_root = project.root();
+        let asts = SourceAsts::from_cache(&solc_cache, project_root);
+
+        let file_metrics = asts
+            .files
+            .iter()
+            .map(|(source_file, ast)| {
+                let source = fs::read_to_string(project_root.join(source_file)).ok();
+                FileMetrics {
+                    source_file: source_file.clone(),
+                    metrics: MetricsResolver::source_unit_metrics(ast, source.as_deref()),
+                }
+            })
+            .collect();
+        Ok(file_metrics)
+    }
+
+    /// Hash of the solc settings a cache entry was compiled with
+    pub fn get_settings_hash(cache_entry: &CacheEntry) -> String {
+        let settings = serde_json::to_vec(&cache_entry.solc_config).unwrap_or_default();
+        format!("0x{}", hex::encode(keccak256(settings)))
+    }
+
     pub fn get_contracts_from_cache(
         solc_cache: SolFilesCache,
         project_root: PathBuf,
@@ -152,6 +264,11 @@ impl ContractResolver {
         let cache_entry = solc_cache.files.get(&cache_entry_path);
         if let Some(cache_entry) = cache_entry {
             let mut contracts: Vec<Contract> = vec![];
+            let source_file = cache_entry_path
+                .strip_prefix(&project_root)
+                .unwrap_or(&cache_entry_path)
+                .to_path_buf();
+            let settings_hash = Self::get_settings_hash(cache_entry);
 
             for (version, artifact_file) in cache_entry.artifacts_versions() {
                 let name = artifact_file
@@ -197,7 +314,7 @@ impl ContractResolver {
 
                 // sort interface first
 
-                imported_contracts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                imported_contracts.sort();
 
                 let bytecode = Self::get_contract_bytecode_from_artifact(&artifact);
                 if bytecode.is_none() {
@@ -206,12 +323,40 @@ impl ContractResolver {
                 let bytecode = bytecode.unwrap();
                 let kind = ContractKind::from(bytecode);
 
+                let abi = artifact.get_abi().map(|abi| abi.into_owned());
+                let selectors = abi.as_ref().map(SelectorTable::from_abi);
+                let standards = abi
+                    .as_ref()
+                    .map(StandardResolver::detect)
+                    .unwrap_or_default();
+                let storage_layout = artifact
+                    .storage_layout
+                    .as_ref()
+                    .map(StorageLayout::from_solc);
+
+                let contract_type = ContractType::from(&kind);
                 let c = Contract {
                     name,
                     kind,
+                    contract_type,
                     version: version.clone(),
-                    // artifact_file: artifact_file.clone(),
+                    project_root: project_root.clone(),
+                    source_file: source_file.clone(),
+                    artifact_file: artifact_file.clone(),
+                    abi,
+                    settings_hash: settings_hash.clone(),
                     imported_contracts,
+                    inheritance: None,
+                    metrics: None,
+                    selectors,
+                    storage_layout,
+                    upgradeability: None,
+                    call_surface: None,
+                    standards,
+                    in_scope: false,
+                    origin: ContractOrigin::Unknown,
+                    library: None,
+                    declared_nsloc: None,
                 };
                 contracts.push(c);
             }
@@ -223,21 +368,80 @@ impl ContractResolver {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ContractCacheEntry {
-    pub name: String,
-    pub version: Version,
-    pub artifact_file: PathBuf,
-    pub imported_files: Vec<PathBuf>,
-}
-
 #[cfg(test)]
 mod test {
 
-    use ethers_solc::{Project, ProjectPathsConfig};
+    use ethers_solc::{artifacts::Settings, ProjectPaths, ProjectPathsConfig, SolcConfig};
+    use std::collections::{BTreeMap, BTreeSet};
 
     use super::*;
 
+    fn cache_entry(artifact_file: &Path, settings: Settings) -> CacheEntry {
+        CacheEntry {
+            last_modification_date: 0,
+            content_hash: String::new(),
+            source_name: PathBuf::from("src/ERC20.sol"),
+            solc_config: SolcConfig { settings },
+            imports: BTreeSet::new(),
+            version_requirement: None,
+            artifacts: BTreeMap::from([(
+                "ERC20".to_owned(),
+                BTreeMap::from([(Version::new(0, 8, 18), artifact_file.to_path_buf())]),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_get_contracts_from_cache_paths_and_abi() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let artifact_file = root.join("out/ERC20.sol/ERC20.json");
+        fs::create_dir_all(artifact_file.parent().unwrap()).unwrap();
+        fs::copy("tests/artifacts/ERC20.json", &artifact_file).unwrap();
+
+        let source_file = root.join("src/ERC20.sol");
+        let entry = cache_entry(&artifact_file, Settings::default());
+        let settings_hash = ContractResolver::get_settings_hash(&entry);
+        let paths = ProjectPaths {
+            artifacts: root.join("out"),
+            build_infos: root.join("out/build-info"),
+            sources: root.join("src"),
+            tests: root.join("test"),
+            scripts: root.join("script"),
+            libraries: BTreeSet::new(),
+        };
+        let solc_cache = SolFilesCache::new(BTreeMap::from([(source_file.clone(), entry)]), paths);
+
+        let contracts =
+            ContractResolver::get_contracts_from_cache(solc_cache, root.clone(), source_file);
+
+        assert_eq!(contracts.len(), 1);
+        let contract = &contracts[0];
+        assert_eq!(contract.name, "ERC20");
+        assert_eq!(contract.project_root, root);
+        assert_eq!(contract.source_file, PathBuf::from("src/ERC20.sol"));
+        assert_eq!(contract.artifact_file, artifact_file);
+        assert!(contract
+            .abi
+            .as_ref()
+            .is_some_and(|abi| abi.function("transfer").is_ok()));
+        assert_eq!(contract.settings_hash, settings_hash);
+    }
+
+    #[test]
+    fn test_get_settings_hash() {
+        let artifact_file = PathBuf::from("out/ERC20.sol/ERC20.json");
+        let hash =
+            |settings| ContractResolver::get_settings_hash(&cache_entry(&artifact_file, settings));
+        let mut optimized = Settings::default();
+        optimized.optimizer.enabled = Some(true);
+        optimized.optimizer.runs = Some(1_000_000);
+
+        assert_eq!(hash(Settings::default()), hash(Settings::default()));
+        assert_eq!(hash(optimized.clone()), hash(optimized.clone()));
+        assert_ne!(hash(Settings::default()), hash(optimized));
+    }
+
     #[test]
     fn test_ContractResolver_get_contracts_from_cache() {
         let project_path = "tests/2023-05-maia";
@@ -290,7 +494,8 @@ mod test {
             )
             .build()
             .unwrap();
-        let contracts = ContractResolver::get_contracts_from_project(&project).unwrap();
+        let contracts =
+            ContractResolver::get_contracts_from_project(&project, &Scope::default()).unwrap();
         for contract in contracts {
             println!("{:#?}", contract);
         }
@@ -0,0 +1,432 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use ethers::utils::{hex, keccak256};
+use rr_logging::warn;
+use semver::Version;
+use serde_json::Value;
+
+use crate::{
+    errors::AppError,
+    types::{Advisory, AdvisoryMatch, Library, LibrarySource, ModifiedFiles, VersionSource},
+};
+
+/// Advisories shipped with the crate, see [`AdvisoryDatabase::load`] to use a newer list
+const BUNDLED_ADVISORIES: &str = include_str!("../../advisories.json");
+
+/// Package names and the directory names their copies are found under. More specific entries
+/// come first.
+const KNOWN_LIBRARIES: &[(&str, &[&str])] = &[
+    (
+        "@openzeppelin/contracts-upgradeable",
+        &[
+            "@openzeppelin/contracts-upgradeable",
+            "openzeppelin-contracts-upgradeable",
+        ],
+    ),
+    (
+        "@openzeppelin/contracts",
+        &[
+            "@openzeppelin/contracts",
+            "openzeppelin-contracts",
+            "openzeppelin",
+        ],
+    ),
+    ("solmate", &["solmate"]),
+    ("solady", &["solady"]),
+    ("forge-std", &["forge-std"]),
+    ("@uniswap/v3-core", &["@uniswap/v3-core", "v3-core"]),
+    (
+        "@uniswap/v3-periphery",
+        &["@uniswap/v3-periphery", "v3-periphery"],
+    ),
+    ("@uniswap/v2-core", &["@uniswap/v2-core", "v2-core"]),
+    (
+        "@chainlink/contracts",
+        &["@chainlink/contracts", "chainlink"],
+    ),
+];
+
+/// Identify the third-party libraries used by a project
+pub struct LibraryResolver {}
+
+impl LibraryResolver {
+    /// Group the source files of a project, relative to its root, by library copy
+    pub fn find_libraries<P>(project_root: P, files: &[PathBuf]) -> Vec<Library>
+    where
+        P: AsRef<Path>,
+    {
+        let project_root = project_root.as_ref();
+
+        let mut copies: BTreeMap<(PathBuf, &str), Vec<PathBuf>> = BTreeMap::new();
+        for file in files.iter() {
+            if let Some((name, path)) = Self::library_of(file) {
+                copies.entry((path, name)).or_default().push(file.clone());
+            }
+        }
+
+        let mut libraries: Vec<Library> = copies
+            .into_iter()
+            .map(|((path, name), mut files)| {
+                files.sort();
+                let absolute_path = project_root.join(&path);
+                let source = Self::source_of(&path, &absolute_path);
+                let version = Self::package_version(&absolute_path)
+                    .map(|version| (version, VersionSource::PackageJson))
+                    .or_else(|| match source {
+                        LibrarySource::GitSubmodule { .. } => Self::git_tag_version(&absolute_path)
+                            .map(|version| (version, VersionSource::GitTag)),
+                        _ => None,
+                    })
+                    .or_else(|| {
+                        Self::header_version(project_root, &files)
+                            .map(|version| (version, VersionSource::Header))
+                    });
+                let modified_files = match source {
+                    LibrarySource::GitSubmodule { .. } => Self::git_modified_files(&absolute_path)
+                        .map(|modified| {
+                            ModifiedFiles::Checked(
+                                modified.into_iter().map(|file| path.join(file)).collect(),
+                            )
+                        })
+                        .unwrap_or(ModifiedFiles::Unknown),
+                    // npm packages are installed from the registry
+                    LibrarySource::NodeModules => ModifiedFiles::Checked(vec![]),
+                    LibrarySource::Vendored => ModifiedFiles::Unknown,
+                };
+
+                Library {
+                    name: name.to_owned(),
+                    version: version.as_ref().map(|(version, _)| version.clone()),
+                    version_source: version.map(|(_, version_source)| version_source),
+                    project_root: project_root.to_path_buf(),
+                    source_hash: Self::source_hash(project_root, &path, &files),
+                    path,
+                    source,
+                    files,
+                    modified_files,
+                }
+            })
+            .collect();
+
+        // vendored copies are compared with another copy of the same library and version, and
+        // stay `Unknown` without one
+        let references: Vec<Library> = libraries
+            .iter()
+            .filter(|library| library.source != LibrarySource::Vendored)
+            .cloned()
+            .collect();
+        for library in libraries
+            .iter_mut()
+            .filter(|library| library.source == LibrarySource::Vendored)
+        {
+            if references
+                .iter()
+                .any(|reference| reference.source_hash == library.source_hash)
+            {
+                library.modified_files = ModifiedFiles::Checked(vec![]);
+                continue;
+            }
+            let reference = references.iter().find(|reference| {
+                reference.name == library.name
+                    && reference.version.is_some()
+                    && reference.version == library.version
+            });
+            if let Some(reference) = reference {
+                library.modified_files = ModifiedFiles::Checked(Self::modified_against(
+                    project_root,
+                    library,
+                    reference,
+                ));
+            }
+        }
+
+        libraries
+    }
+
+    /// Name and root directory of the library a file belongs to
+    pub fn library_of(file: &Path) -> Option<(&'static str, PathBuf)> {
+        let components: Vec<String> = file
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+
+        for (name, markers) in KNOWN_LIBRARIES.iter() {
+            for marker in markers.iter() {
+                let marker: Vec<&str> = marker.split('/').collect();
+                let position = components
+                    .windows(marker.len())
+                    .position(|window| window.iter().zip(marker.iter()).all(|(a, b)| a == b));
+                if let Some(position) = position {
+                    let root: PathBuf = components[..position + marker.len()].iter().collect();
+                    return Some((name, root));
+                }
+            }
+        }
+        None
+    }
+
+    fn source_of(path: &Path, absolute_path: &Path) -> LibrarySource {
+        if path.starts_with("node_modules") {
+            LibrarySource::NodeModules
+        } else if absolute_path.join(".git").exists() {
+            LibrarySource::GitSubmodule {
+                commit: git(absolute_path, &["rev-parse", "HEAD"]),
+            }
+        } else {
+            LibrarySource::Vendored
+        }
+    }
+
+    /// `version` of the library's own `package.json`
+    fn package_version(library_root: &Path) -> Option<Version> {
+        let content = fs::read_to_string(library_root.join("package.json")).ok()?;
+        let package: Value = serde_json::from_str(&content).ok()?;
+        Version::parse(package.get("version")?.as_str()?).ok()
+    }
+
+    fn git_tag_version(library_root: &Path) -> Option<Version> {
+        let tag = git(library_root, &["describe", "--tags", "--abbrev=0"])?;
+        Version::parse(tag.trim_start_matches('v')).ok()
+    }
+
+    /// Version from headers such as `// OpenZeppelin Contracts (last updated v4.9.0)`. Files not
+    /// updated in a release keep an older header, so this is only a lower bound.
+    fn header_version(project_root: &Path, files: &[PathBuf]) -> Option<Version> {
+        files
+            .iter()
+            .filter_map(|file| fs::read_to_string(project_root.join(file)).ok())
+            .filter_map(|content| {
+                content
+                    .lines()
+                    .take(5)
+                    .filter(|line| line.trim_start().starts_with("//"))
+                    .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ')'))
+                    .filter_map(|word| word.strip_prefix('v'))
+                    .find_map(|version| Version::parse(version).ok())
+            })
+            .max()
+    }
+
+    fn git_modified_files(library_root: &Path) -> Option<Vec<PathBuf>> {
+        let status = git(library_root, &["status", "--porcelain"])?;
+        Some(
+            status
+                .lines()
+                .filter_map(|line| line.get(3..))
+                .map(PathBuf::from)
+                .collect(),
+        )
+    }
+
+    fn modified_against(
+        project_root: &Path,
+        library: &Library,
+        reference: &Library,
+    ) -> Vec<PathBuf> {
+        library
+            .files
+            .iter()
+            .filter(|file| {
+                let relative = file.strip_prefix(&library.path).unwrap_or(file);
+                let reference_file = project_root.join(&reference.path).join(relative);
+                match fs::read(&reference_file) {
+                    Ok(reference_content) => {
+                        fs::read(project_root.join(file)).ok() != Some(reference_content)
+                    }
+                    // only in the vendored copy
+                    Err(_) => true,
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn source_hash(project_root: &Path, library_path: &Path, files: &[PathBuf]) -> String {
+        let mut data: Vec<u8> = vec![];
+        for file in files.iter() {
+            let relative = file.strip_prefix(library_path).unwrap_or(file);
+            data.extend(relative.to_string_lossy().as_bytes());
+            data.extend(keccak256(
+                fs::read(project_root.join(file)).unwrap_or_default(),
+            ));
+        }
+        format!("0x{}", hex::encode(keccak256(data)))
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_owned(),
+    )
+}
+
+/// Advisories of known library versions
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDatabase {
+    pub advisories: Vec<Advisory>,
+}
+
+impl AdvisoryDatabase {
+    /// The list bundled with the crate
+    pub fn bundled() -> Self {
+        let advisories = serde_json::from_str(BUNDLED_ADVISORIES).unwrap_or_else(|e| {
+            warn!("Invalid bundled advisories: {e:?}");
+            vec![]
+        });
+        Self { advisories }
+    }
+
+    /// Load a JSON list of advisories, e.g. a locally updated copy of `advisories.json`
+    pub fn load<P>(path: P) -> Result<Self, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        let content = fs::read_to_string(path)?;
+        Ok(Self {
+            advisories: serde_json::from_str(&content)?,
+        })
+    }
+
+    pub fn matches(&self, libraries: &[Library]) -> Vec<AdvisoryMatch> {
+        libraries
+            .iter()
+            .flat_map(|library| {
+                self.advisories
+                    .iter()
+                    .filter(|advisory| advisory.affects(library))
+                    .map(|advisory| AdvisoryMatch {
+                        project_root: library.project_root.clone(),
+                        library: library.name.clone(),
+                        version: library.version.clone().unwrap_or(Version::new(0, 0, 0)),
+                        approximate_version: library.is_version_approximate(),
+                        advisory: advisory.clone(),
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(root: &Path, file: &str, content: &str) -> PathBuf {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        PathBuf::from(file)
+    }
+
+    #[test]
+    fn test_library_of() {
+        assert_eq!(
+            LibraryResolver::library_of(Path::new(
+                "lib/openzeppelin-contracts-upgradeable/contracts/proxy/utils/Initializable.sol"
+            )),
+            Some((
+                "@openzeppelin/contracts-upgradeable",
+                PathBuf::from("lib/openzeppelin-contracts-upgradeable")
+            ))
+        );
+        assert_eq!(
+            LibraryResolver::library_of(Path::new(
+                "node_modules/@openzeppelin/contracts/token/ERC20/ERC20.sol"
+            )),
+            Some((
+                "@openzeppelin/contracts",
+                PathBuf::from("node_modules/@openzeppelin/contracts")
+            ))
+        );
+        assert_eq!(
+            LibraryResolver::library_of(Path::new("src/Vault.sol")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_libraries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "node_modules/@openzeppelin/contracts/package.json",
+            r#"{ "version": "4.8.0" }"#,
+        );
+        let files = vec![
+            write(
+                root,
+                "node_modules/@openzeppelin/contracts/token/ERC20/ERC20.sol",
+                "contract ERC20 {}",
+            ),
+            write(
+                root,
+                "src/vendor/openzeppelin/token/ERC20/ERC20.sol",
+                "// OpenZeppelin Contracts (last updated v4.8.0) (token/ERC20/ERC20.sol)\ncontract ERC20 { uint256 fee; }",
+            ),
+            write(root, "src/Vault.sol", "contract Vault {}"),
+        ];
+
+        let libraries = LibraryResolver::find_libraries(root, &files);
+
+        assert_eq!(libraries.len(), 2);
+        let node_modules = &libraries[0];
+        assert_eq!(node_modules.source, LibrarySource::NodeModules);
+        assert_eq!(node_modules.version, Some(Version::new(4, 8, 0)));
+        let vendored = &libraries[1];
+        assert_eq!(vendored.source, LibrarySource::Vendored);
+        assert_eq!(vendored.path, PathBuf::from("src/vendor/openzeppelin"));
+        assert_eq!(vendored.version, Some(Version::new(4, 8, 0)));
+        assert!(vendored.is_version_approximate());
+        assert!(vendored.is_modified());
+
+        let matches = AdvisoryDatabase::bundled().matches(&libraries[..1]);
+        assert!(matches
+            .iter()
+            .any(|item| item.advisory.id == "GHSA-mx2q-35m2-x2rh"));
+        assert!(!matches
+            .iter()
+            .any(|item| item.advisory.id == "GHSA-4h98-2769-gh6h"));
+    }
+
+    #[test]
+    fn test_find_single_vendored_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = vec![write(
+            root,
+            "src/vendor/openzeppelin-contracts-upgradeable/proxy/utils/Initializable.sol",
+            "// OpenZeppelin Contracts (last updated v4.7.0) (proxy/utils/Initializable.sol)\ncontract Initializable {}",
+        )];
+
+        let libraries = LibraryResolver::find_libraries(root, &files);
+
+        assert_eq!(libraries.len(), 1);
+        let vendored = &libraries[0];
+        assert_eq!(vendored.name, "@openzeppelin/contracts-upgradeable");
+        assert_eq!(vendored.modified_files, ModifiedFiles::Unknown);
+        assert!(!vendored.is_modified());
+        assert_eq!(vendored.version_source, Some(VersionSource::Header));
+
+        let matches = AdvisoryDatabase::bundled().matches(&libraries);
+        let merkle_proof = matches
+            .iter()
+            .find(|item| item.advisory.id == "GHSA-wprv-93r4-jj2p")
+            .unwrap();
+        assert!(merkle_proof.approximate_version);
+    }
+}
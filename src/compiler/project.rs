@@ -1,24 +1,32 @@
 use std::{
-    collections::HashSet,
-    env,
     fs::{self, File, OpenOptions},
     io::{Read, Write},
     path::{Path, PathBuf},
     process::Command,
+    str::FromStr,
 };
 
 use ethers_solc::{
-    artifacts::ExpressionOrVariableDeclarationStatement, cache::SOLIDITY_FILES_CACHE_FILENAME,
-    Project, ProjectPathsConfig,
+    artifacts::{
+        output_selection::ContractOutputSelection, ExpressionOrVariableDeclarationStatement,
+    },
+    cache::SOLIDITY_FILES_CACHE_FILENAME,
+    ConfigurableArtifacts, Project, ProjectPathsConfig,
 };
-use rr_logging::{error, info, instrument, tracing};
-use walkdir::WalkDir;
+use rr_logging::{error, info, instrument, tracing, warn};
+use serde::Deserialize;
 
 use crate::{
     errors::AppError,
     types::{FoundryConfig, RepoUri},
 };
 
+use super::{find_all_project_roots, ProjectRoot, ProjectRootOptions};
+
+/// Compiler outputs requested on top of the defaults, kept in the artifacts. Must match the
+/// `--extra-output` flags of [`compile_project`].
+const EXTRA_OUTPUT: [ContractOutputSelection; 1] = [ContractOutputSelection::StorageLayout];
+
 #[derive(Debug, Clone)]
 pub enum ProjectType {
     Foundry,
@@ -27,13 +35,49 @@ pub enum ProjectType {
     Unknown,
 }
 
+/// Tool a project is set up with when it has both a Hardhat config and a `foundry.toml`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildBackend {
+    /// Install the npm dependencies and build through hardhat-foundry
+    #[default]
+    Hardhat,
+    /// Build with forge alone, skipping `npm install`
+    Foundry,
+}
+
+impl FromStr for BuildBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hardhat" => Ok(Self::Hardhat),
+            "foundry" => Ok(Self::Foundry),
+            _ => Err(format!("Unknown build backend {s:?}")),
+        }
+    }
+}
+
 impl ProjectType {
     pub fn from_repo_dir<P>(repo_dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self::detect(repo_dir, BuildBackend::default())
+    }
+
+    /// Project type of a directory from its config files, `backend` picks the type of
+    /// directories with both a Hardhat and a Foundry config
+    pub fn detect<P>(repo_dir: P, backend: BuildBackend) -> Self
     where
         P: AsRef<Path>,
     {
         let repo_dir = repo_dir.as_ref().to_path_buf();
 
+        if backend == BuildBackend::Foundry && repo_dir.join("foundry.toml").exists() {
+            return Self::Foundry;
+        }
+
         if repo_dir.join("hardhat.config.js").exists()
             || repo_dir.join("hardhat.config.ts").exists()
         {
@@ -51,39 +95,143 @@ impl ProjectType {
     }
 }
 
+/// Stage of the pipeline a project root is going through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectStage {
+    ResolveDependencies,
+    Compile,
+    ParseConfig,
+    ExtractContracts,
+}
+
+/// A failure of a single project root, which doesn't abort the other roots
+#[derive(Debug, Clone)]
+pub struct ProjectDiagnostic {
+    pub project_root: PathBuf,
+    pub stage: ProjectStage,
+    pub reason: String,
+}
+
+impl ProjectDiagnostic {
+    pub fn new<P>(project_root: P, stage: ProjectStage, error: &AppError) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            stage,
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// Result of resolving and compiling a single project root
+#[derive(Debug)]
+pub struct ProjectRootReport {
+    pub root: ProjectRoot,
+    pub resolved: bool,
+    pub compiled: bool,
+    pub project: Option<Project>,
+    pub failure: Option<ProjectDiagnostic>,
+}
+
+impl ProjectRootReport {
+    fn new(root: ProjectRoot) -> Self {
+        Self {
+            root,
+            resolved: false,
+            compiled: false,
+            project: None,
+            failure: None,
+        }
+    }
+
+    fn fail(mut self, stage: ProjectStage, error: AppError) -> Self {
+        warn!(
+            "Project root {:?} failed at {:?}: {}",
+            self.root.path, stage, error
+        );
+        self.failure = Some(ProjectDiagnostic::new(&self.root.path, stage, &error));
+        self
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
 /// Resolve project
 pub struct ProjectResolver {}
 
 impl ProjectResolver {
+    pub fn parse<P>(repo_dir: P) -> Result<Vec<ProjectRootReport>, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::parse_with_options(repo_dir, &ProjectRootOptions::default())
+    }
+
+    /// Resolve and compile every project root of the repo. A failing root is reported
+    /// and skipped, only the root discovery itself can fail the whole repo.
     #[instrument(skip_all)]
-    pub fn parse<P>(repo_dir: P) -> Result<Vec<Project>, AppError>
+    pub fn parse_with_options<P>(
+        repo_dir: P,
+        options: &ProjectRootOptions,
+    ) -> Result<Vec<ProjectRootReport>, AppError>
     where
         P: AsRef<Path>,
     {
-        let all_project_roots = find_all_project_roots(repo_dir.as_ref());
-        let mut projects: Vec<Project> = vec![];
-
-        for project_root in all_project_roots.iter() {
-            info!("Project root {:#?}", project_root);
-            let project_type = ProjectType::from_repo_dir(&project_root);
-            info!("Project type {:#?}", project_type);
-
-            resolve_dependencies(&project_root, &project_type)?;
-            compile_project(&project_root, &project_type)?;
-
-            let project_paths_config = match project_type {
-                ProjectType::Foundry => Self::parse_foundry_config(&project_root)?,
-                ProjectType::Hardhat => Self::parse_foundry_config(&project_root)?,
-                _ => {
-                    return Err(AppError::UnsupportedProjectType(project_type.clone()));
-                }
-            };
+        let discovery = find_all_project_roots(repo_dir.as_ref(), options)?;
+
+        let reports = discovery
+            .roots
+            .into_iter()
+            .map(|project_root| {
+                info!(
+                    "Project root {:#?}, accepted because {}",
+                    project_root.path, project_root.reason
+                );
+                info!("Project type {:#?}", project_root.project_type);
+                Self::parse_project_root(project_root)
+            })
+            .collect();
+
+        Ok(reports)
+    }
+
+    fn parse_project_root(project_root: ProjectRoot) -> ProjectRootReport {
+        let project_type = project_root.project_type.clone();
+        let root_path = project_root.path.clone();
+        let mut report = ProjectRootReport::new(project_root);
 
-            let project = Project::builder().paths(project_paths_config).build()?;
-            projects.push(project);
+        if let Err(e) = resolve_dependencies(&root_path, &project_type) {
+            return report.fail(ProjectStage::ResolveDependencies, e);
         }
+        report.resolved = true;
 
-        Ok(projects)
+        if let Err(e) = compile_project(&root_path, &project_type) {
+            return report.fail(ProjectStage::Compile, e);
+        }
+        report.compiled = true;
+
+        let project_paths_config = match project_type {
+            ProjectType::Foundry | ProjectType::Hardhat => Self::parse_foundry_config(&root_path),
+            _ => Err(AppError::UnsupportedProjectType(project_type.clone())),
+        };
+        let project = project_paths_config.and_then(|paths| {
+            Ok(Project::builder()
+                .paths(paths)
+                .artifacts(ConfigurableArtifacts::new(EXTRA_OUTPUT, []))
+                .build()?)
+        });
+
+        match project {
+            Ok(project) => {
+                report.project = Some(project);
+                report
+            }
+            Err(e) => report.fail(ProjectStage::ParseConfig, e),
+        }
     }
 
     pub fn parse_foundry_config<P>(repo_dir: P) -> Result<ProjectPathsConfig, AppError>
@@ -124,6 +272,11 @@ impl ProjectResolver {
                 project_path_config = project_path_config.tests(test_path);
             }
 
+            if let Some(script_config) = parsed_toml.profile.default.script {
+                let script_path = repo_dir.join(script_config);
+                project_path_config = project_path_config.scripts(script_path);
+            }
+
             let out_config = parsed_toml.profile.default.out.unwrap_or("out".to_owned());
             let out_path = repo_dir.join(out_config);
             project_path_config = project_path_config.artifacts(out_path);
@@ -156,55 +309,18 @@ impl ProjectResolver {
     }
 }
 
-pub fn project_dir_from_uri(repo_uri: &str) -> PathBuf {
-    let dir_name = RepoUri::from(repo_uri.to_string());
-    let dir_name = dir_name.to_dir_name().unwrap();
-    let dir_path = PathBuf::from(env::current_dir().unwrap())
-        .join("contests")
-        .join(dir_name);
-
-    dir_path
-}
-
-/// Find directories that contains config file
-pub fn find_all_project_roots<P>(repo_dir: P) -> Vec<PathBuf>
+/// Directory of the repo clone in the workspace, named after the repo
+pub fn project_dir_from_uri<P>(workspace_dir: P, repo_uri: &str) -> Result<PathBuf, AppError>
 where
     P: AsRef<Path>,
 {
-    let repo_dir = repo_dir.as_ref().to_path_buf();
-    let mut project_root_mapping: HashSet<PathBuf> = HashSet::new();
-
-    let ignore_dirs = vec!["lib", "libs", "node_modules"];
-    let valid_config_files = vec![
-        "foundry.toml",
-        "hardhat.config.js",
-        "hardhat.config.ts",
-        "truffle-config.js",
-    ];
-
-    let mut childs: Vec<PathBuf> = vec![];
-    for entry in WalkDir::new(repo_dir.clone())
-        .max_depth(2) // we only support 2 levels depth
-        .into_iter()
-        .filter_entry(|entry| {
-            // filter out library directories
-            let dir_name = entry.file_name().to_string_lossy().to_string();
-            !(entry.file_type().is_dir() && ignore_dirs.contains(&dir_name.as_str()))
-        })
-        .filter_map(Result::ok)
-    {
-        if entry.file_type().is_file()
-            && valid_config_files
-                .contains(&entry.file_name().to_string_lossy().to_string().as_str())
-        {
-            let child_dir = entry.path().parent().unwrap().to_path_buf();
-            if !project_root_mapping.contains(&child_dir) {
-                project_root_mapping.insert(child_dir.clone());
-                childs.push(child_dir);
-            }
-        }
-    }
-    childs
+    let dir_name = RepoUri::from(repo_uri.to_string());
+    let dir_name = dir_name
+        .to_dir_name()
+        .ok_or_else(|| AppError::InvalidRepoUri(repo_uri.to_owned()))?;
+    let dir_path = workspace_dir.as_ref().join(dir_name);
+
+    Ok(dir_path)
 }
 
 #[instrument(fields(repo_dir, project_type))]
@@ -307,7 +423,7 @@ where
     match project_type {
         ProjectType::Foundry | ProjectType::Hardhat => {
             let output = Command::new("forge")
-                .args(&["build"])
+                .args(&["build", "--extra-output", "storageLayout"])
                 .current_dir(&repo_dir)
                 .output()?;
 
@@ -390,6 +506,48 @@ mod test {
         compile_project(&repo_dir, &project_type).unwrap();
     }
 
+    #[test]
+    fn test_project_type_detect() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        fs::write(repo_dir.path().join("hardhat.config.ts"), "").unwrap();
+        fs::write(repo_dir.path().join("foundry.toml"), "").unwrap();
+
+        assert!(matches!(
+            ProjectType::detect(repo_dir.path(), BuildBackend::Hardhat),
+            ProjectType::Hardhat
+        ));
+        assert!(matches!(
+            ProjectType::detect(repo_dir.path(), BuildBackend::Foundry),
+            ProjectType::Foundry
+        ));
+
+        fs::remove_file(repo_dir.path().join("foundry.toml")).unwrap();
+        assert!(matches!(
+            ProjectType::detect(repo_dir.path(), BuildBackend::Foundry),
+            ProjectType::Hardhat
+        ));
+    }
+
+    #[test]
+    fn test_project_resolver_parse_keeps_going_on_failure() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        for package in ["a", "b"] {
+            let package_dir = repo_dir.path().join(package);
+            fs::create_dir_all(&package_dir).unwrap();
+            fs::write(package_dir.join("truffle-config.js"), "").unwrap();
+        }
+
+        let reports = ProjectResolver::parse(repo_dir.path()).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        for report in reports {
+            assert!(report.resolved);
+            assert!(!report.compiled);
+            assert!(report.project.is_none());
+            assert_eq!(report.failure.unwrap().stage, ProjectStage::Compile);
+        }
+    }
+
     #[test]
     fn test_project_resolver_parse() {
         let repo_dir = PathBuf::from("contests/2023-06-dodo");
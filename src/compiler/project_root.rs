@@ -0,0 +1,338 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use rr_logging::{info, instrument, tracing};
+
+use crate::{errors::AppError, types::FoundryConfig};
+
+use super::{BuildBackend, ProjectType};
+
+/// Config files that mark a directory as a project root
+pub const PROJECT_CONFIG_FILES: [&str; 4] = [
+    "foundry.toml",
+    "hardhat.config.js",
+    "hardhat.config.ts",
+    "truffle-config.js",
+];
+
+/// Options used by [`find_all_project_roots`]
+#[derive(Debug, Clone)]
+pub struct ProjectRootOptions {
+    /// Max depth of a config file, relative to the repo directory
+    pub max_depth: usize,
+    /// Globs of directories to skip, matched against the path relative to the repo directory
+    pub ignore_globs: Vec<String>,
+    /// Skip everything ignored by `.gitignore`
+    pub respect_gitignore: bool,
+    pub backend: BuildBackend,
+}
+
+impl Default for ProjectRootOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            ignore_globs: vec![
+                "**/lib".to_owned(),
+                "**/libs".to_owned(),
+                "**/node_modules".to_owned(),
+            ],
+            respect_gitignore: true,
+            backend: BuildBackend::default(),
+        }
+    }
+}
+
+impl ProjectRootOptions {
+    fn ignore_set(&self) -> Result<GlobSet, AppError> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in self.ignore_globs.iter() {
+            let glob =
+                Glob::new(pattern).map_err(|e| AppError::InvalidIgnoreGlob(e.to_string()))?;
+            builder.add(glob);
+        }
+        builder
+            .build()
+            .map_err(|e| AppError::InvalidIgnoreGlob(e.to_string()))
+    }
+}
+
+/// Why a directory was accepted or skipped as a project root
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectRootReason {
+    /// A config file was found at `depth` below the repo directory
+    ConfigFile { config_file: PathBuf, depth: usize },
+    /// The directory lives inside the libs directory of another root
+    NestedInLibs {
+        parent_root: PathBuf,
+        libs_dir: PathBuf,
+    },
+}
+
+impl Display for ProjectRootReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConfigFile { config_file, depth } => {
+                write!(f, "found {:?} at depth {}", config_file, depth)
+            }
+            Self::NestedInLibs {
+                parent_root,
+                libs_dir,
+            } => write!(
+                f,
+                "nested inside libs dir {:?} of root {:?}",
+                libs_dir, parent_root
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectRoot {
+    pub path: PathBuf,
+    pub project_type: ProjectType,
+    pub reason: ProjectRootReason,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkippedProjectRoot {
+    pub path: PathBuf,
+    pub reason: ProjectRootReason,
+}
+
+/// Result of [`find_all_project_roots`]
+#[derive(Debug, Clone, Default)]
+pub struct ProjectRootDiscovery {
+    pub roots: Vec<ProjectRoot>,
+    pub skipped: Vec<SkippedProjectRoot>,
+}
+
+/// Find directories that contains config file
+#[instrument(skip(options))]
+pub fn find_all_project_roots<P>(
+    repo_dir: P,
+    options: &ProjectRootOptions,
+) -> Result<ProjectRootDiscovery, AppError>
+where
+    P: AsRef<Path> + std::fmt::Debug,
+{
+    let repo_dir = repo_dir.as_ref().to_path_buf();
+    let ignore_set = options.ignore_set()?;
+
+    let filter_root = repo_dir.clone();
+    let walker = WalkBuilder::new(&repo_dir)
+        .max_depth(Some(options.max_depth))
+        .git_ignore(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .git_global(false)
+        .parents(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if !is_dir {
+                return true;
+            }
+            // filter out library directories
+            match entry.path().strip_prefix(&filter_root) {
+                Ok(relative_path) if relative_path.as_os_str().is_empty() => true,
+                Ok(relative_path) => !ignore_set.is_match(relative_path),
+                Err(_) => true,
+            }
+        })
+        .build();
+
+    let mut candidates: Vec<ProjectRoot> = vec![];
+    for entry in walker.filter_map(Result::ok) {
+        let is_file = entry.file_type().map(|t| t.is_file()).unwrap_or(false);
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !is_file || !PROJECT_CONFIG_FILES.contains(&file_name.as_str()) {
+            continue;
+        }
+
+        let child_dir = entry.path().parent().unwrap().to_path_buf();
+        if candidates.iter().any(|root| root.path == child_dir) {
+            continue;
+        }
+
+        candidates.push(ProjectRoot {
+            project_type: ProjectType::detect(&child_dir, options.backend),
+            path: child_dir,
+            reason: ProjectRootReason::ConfigFile {
+                config_file: entry.path().to_path_buf(),
+                depth: entry.depth(),
+            },
+        });
+    }
+
+    let mut discovery = ProjectRootDiscovery::default();
+    for candidate in candidates.iter() {
+        let parent = candidates.iter().find_map(|other| {
+            if other.path == candidate.path {
+                return None;
+            }
+            libs_dirs(&other.path, &other.project_type)
+                .into_iter()
+                .find(|libs_dir| candidate.path.starts_with(libs_dir))
+                .map(|libs_dir| (other.path.clone(), libs_dir))
+        });
+
+        match parent {
+            Some((parent_root, libs_dir)) => {
+                let reason = ProjectRootReason::NestedInLibs {
+                    parent_root,
+                    libs_dir,
+                };
+                info!("Skipping project root {:?}: {}", candidate.path, reason);
+                discovery.skipped.push(SkippedProjectRoot {
+                    path: candidate.path.clone(),
+                    reason,
+                });
+            }
+            None => {
+                info!(
+                    "Accepted project root {:?}: {}",
+                    candidate.path, candidate.reason
+                );
+                discovery.roots.push(candidate.clone());
+            }
+        }
+    }
+
+    Ok(discovery)
+}
+
+/// Directories holding the dependencies of a project root
+fn libs_dirs(project_root: &Path, project_type: &ProjectType) -> Vec<PathBuf> {
+    let mut libs: Vec<PathBuf> = vec![project_root.join("node_modules")];
+
+    if matches!(project_type, ProjectType::Foundry | ProjectType::Hardhat) {
+        let foundry_libs = fs::read_to_string(project_root.join("foundry.toml"))
+            .ok()
+            .and_then(|content| toml::from_str::<FoundryConfig>(&content).ok())
+            .and_then(|config| config.profile.default.libs)
+            .unwrap_or(vec!["lib".to_owned()]);
+        libs.extend(foundry_libs.into_iter().map(|lib| project_root.join(lib)));
+    }
+
+    libs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    fn touch<P: AsRef<Path>>(path: P, content: &str) {
+        let path = path.as_ref();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_all_project_roots_monorepo() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        let repo = repo_dir.path();
+        touch(repo.join("packages/core/contracts/foundry.toml"), "");
+        touch(
+            repo.join("packages/core/contracts/lib/forge-std/foundry.toml"),
+            "",
+        );
+        touch(repo.join("packages/periphery/hardhat.config.ts"), "");
+
+        let discovery = find_all_project_roots(repo, &ProjectRootOptions::default()).unwrap();
+        let roots: Vec<PathBuf> = discovery.roots.into_iter().map(|root| root.path).collect();
+
+        assert_eq!(
+            roots,
+            vec![
+                repo.join("packages/core/contracts"),
+                repo.join("packages/periphery")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_all_project_roots_max_depth() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        let repo = repo_dir.path();
+        touch(repo.join("foundry.toml"), "");
+        touch(repo.join("a/b/c/foundry.toml"), "");
+
+        let options = ProjectRootOptions {
+            max_depth: 2,
+            ..Default::default()
+        };
+        let discovery = find_all_project_roots(repo, &options).unwrap();
+
+        assert_eq!(discovery.roots.len(), 1);
+        assert_eq!(
+            discovery.roots[0].reason,
+            ProjectRootReason::ConfigFile {
+                config_file: repo.join("foundry.toml"),
+                depth: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_all_project_roots_skip_nested_libs() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        let repo = repo_dir.path();
+        touch(
+            repo.join("foundry.toml"),
+            "[profile.default]\nlibs = [\"dependencies\"]\n",
+        );
+        touch(repo.join("dependencies/solmate/foundry.toml"), "");
+
+        let discovery = find_all_project_roots(repo, &ProjectRootOptions::default()).unwrap();
+
+        assert_eq!(discovery.roots.len(), 1);
+        assert_eq!(discovery.skipped.len(), 1);
+        assert_eq!(
+            discovery.skipped[0].reason,
+            ProjectRootReason::NestedInLibs {
+                parent_root: repo.to_path_buf(),
+                libs_dir: repo.join("dependencies"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_all_project_roots_gitignore_and_globs() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        let repo = repo_dir.path();
+        touch(repo.join(".gitignore"), "generated/\n");
+        touch(repo.join("generated/foundry.toml"), "");
+        touch(repo.join("examples/foundry.toml"), "");
+        touch(repo.join("core/foundry.toml"), "");
+
+        let options = ProjectRootOptions {
+            ignore_globs: vec!["examples".to_owned()],
+            ..Default::default()
+        };
+        let discovery = find_all_project_roots(repo, &options).unwrap();
+        let roots: Vec<PathBuf> = discovery.roots.into_iter().map(|root| root.path).collect();
+        assert_eq!(roots, vec![repo.join("core")]);
+
+        let options = ProjectRootOptions {
+            respect_gitignore: false,
+            ..Default::default()
+        };
+        let discovery = find_all_project_roots(repo, &options).unwrap();
+        assert_eq!(discovery.roots.len(), 3);
+    }
+
+    #[test]
+    fn test_find_all_project_roots_invalid_glob() {
+        let options = ProjectRootOptions {
+            ignore_globs: vec!["a/**{".to_owned()],
+            ..Default::default()
+        };
+        assert!(find_all_project_roots("tests", &options).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rr_logging::info;
+
+use crate::types::{Scope, ScopeEntry};
+
+const SCOPE_FILES: [&str; 2] = ["scope.txt", "SCOPE.txt"];
+const README_FILES: [&str; 2] = ["README.md", "readme.md"];
+
+/// Resolve the contest scope of a repo
+pub struct ScopeResolver {}
+
+impl ScopeResolver {
+    /// Read `scope.txt` and the README scope table from the repo directory. Files listed in
+    /// `scope.txt` take precedence, the README table fills in their nSLOC.
+    pub fn parse<P>(repo_dir: P) -> Scope
+    where
+        P: AsRef<Path>,
+    {
+        let repo_dir = repo_dir.as_ref();
+        // project roots are canonicalized, so are the source files they are compared to
+        let mut scope = Scope {
+            repo_dir: fs::canonicalize(repo_dir).unwrap_or_else(|_| repo_dir.to_path_buf()),
+            ..Default::default()
+        };
+
+        if let Some(content) = Self::read_first(repo_dir, &SCOPE_FILES) {
+            for entry in Self::parse_scope_txt(&content) {
+                scope.merge(entry);
+            }
+        }
+
+        if let Some(content) = Self::read_first(repo_dir, &README_FILES) {
+            let from_scope_txt = !scope.is_empty();
+            for entry in Self::parse_readme_table(&content) {
+                if from_scope_txt && !scope.entries.iter().any(|item| item.path == entry.path) {
+                    continue;
+                }
+                scope.merge(entry);
+            }
+        }
+
+        info!("Found {:?} files in scope", scope.entries.len());
+        scope
+    }
+
+    /// Parse a `scope.txt`, one Solidity file per line
+    pub fn parse_scope_txt(content: &str) -> Vec<ScopeEntry> {
+        content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| line.ends_with(".sol"))
+            .map(|line| ScopeEntry {
+                path: normalize_scope_path(line),
+                nsloc: None,
+            })
+            .collect()
+    }
+
+    /// Parse the markdown tables of a README whose header has a `nSLOC` or `SLOC` column
+    pub fn parse_readme_table(content: &str) -> Vec<ScopeEntry> {
+        let mut result: Vec<ScopeEntry> = vec![];
+        let mut sloc_column: Option<usize> = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if !line.starts_with('|') {
+                sloc_column = None;
+                continue;
+            }
+
+            let cells = split_table_row(line);
+            if is_separator_row(&cells) {
+                continue;
+            }
+
+            let Some(column) = sloc_column else {
+                sloc_column = find_sloc_column(&cells);
+                continue;
+            };
+
+            let Some(path) = cells.iter().find_map(|cell| path_from_cell(cell)) else {
+                continue;
+            };
+            let nsloc = cells.get(column).and_then(|cell| {
+                let digits: String = cell.chars().filter(|c| c.is_ascii_digit()).collect();
+                digits.parse::<usize>().ok()
+            });
+
+            result.push(ScopeEntry { path, nsloc });
+        }
+
+        result
+    }
+
+    fn read_first(repo_dir: &Path, file_names: &[&str]) -> Option<String> {
+        file_names
+            .iter()
+            .find_map(|file_name| fs::read_to_string(repo_dir.join(file_name)).ok())
+    }
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+    let line = line.trim_start_matches('|').trim_end_matches('|');
+    line.split('|').map(|cell| cell.trim().to_owned()).collect()
+}
+
+fn is_separator_row(cells: &[String]) -> bool {
+    cells
+        .iter()
+        .all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+}
+
+fn find_sloc_column(cells: &[String]) -> Option<usize> {
+    let cells: Vec<String> = cells.iter().map(|cell| cell.to_lowercase()).collect();
+    cells
+        .iter()
+        .position(|cell| cell.contains("nsloc"))
+        .or_else(|| cells.iter().position(|cell| cell.contains("sloc")))
+}
+
+/// Extract a Solidity file path from a table cell, e.g. `[src/A.sol](https://github.com/org/repo/blob/main/src/A.sol)`
+fn path_from_cell(cell: &str) -> Option<PathBuf> {
+    let cell = cell.replace('`', "");
+
+    let mut candidates: Vec<&str> = vec![];
+    if let (Some(start), Some(end)) = (cell.find('['), cell.find("](")) {
+        if start < end {
+            candidates.push(&cell[start + 1..end]);
+            if let Some(close) = cell[end + 2..].find(')') {
+                candidates.push(&cell[end + 2..end + 2 + close]);
+            }
+        }
+    }
+    candidates.push(&cell);
+
+    let candidate = candidates
+        .into_iter()
+        .map(|candidate| candidate.trim())
+        .find(|candidate| candidate.ends_with(".sol"))?;
+
+    let candidate = match candidate.find("/blob/") {
+        // skip the branch name after `/blob/`
+        Some(index) => candidate[index + "/blob/".len()..]
+            .split_once('/')
+            .map(|(_, path)| path)
+            .unwrap_or(candidate),
+        None => candidate,
+    };
+
+    Some(normalize_scope_path(candidate))
+}
+
+fn normalize_scope_path(path: &str) -> PathBuf {
+    PathBuf::from(path.trim().trim_start_matches("./").trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_scope_txt() {
+        let content = "./src/Vault.sol\nsrc/libraries/Math.sol\n\nREADME.md\n";
+        let entries = ScopeResolver::parse_scope_txt(content);
+
+        assert_eq!(
+            entries,
+            vec![
+                ScopeEntry {
+                    path: PathBuf::from("src/Vault.sol"),
+                    nsloc: None
+                },
+                ScopeEntry {
+                    path: PathBuf::from("src/libraries/Math.sol"),
+                    nsloc: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_readme_table() {
+        let content = r#"
+# Scope
+
+| Contract | SLOC | Purpose | Libraries used |
+| ----------- | ----------- | ----------- | ----------- |
+| [src/Vault.sol](https://github.com/code-423n4/2023-05-maia/blob/main/src/Vault.sol) | 1,234 | Vault | [`@openzeppelin/*`](https://openzeppelin.com) |
+| [`src/Math.sol`](src/Math.sol) | 56 | Math | |
+
+| Contract | Reason |
+| --- | --- |
+| src/Ignored.sol | out of scope |
+"#;
+        let entries = ScopeResolver::parse_readme_table(content);
+
+        assert_eq!(
+            entries,
+            vec![
+                ScopeEntry {
+                    path: PathBuf::from("src/Vault.sol"),
+                    nsloc: Some(1234)
+                },
+                ScopeEntry {
+                    path: PathBuf::from("src/Math.sol"),
+                    nsloc: Some(56)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scope_resolver_parse() {
+        let repo_dir = tempfile::tempdir().unwrap();
+        fs::write(repo_dir.path().join("scope.txt"), "src/Vault.sol\n").unwrap();
+        fs::write(
+            repo_dir.path().join("README.md"),
+            "| File | nSLOC |\n|---|---|\n| src/Vault.sol | 10 |\n| src/Other.sol | 20 |\n",
+        )
+        .unwrap();
+
+        let scope = ScopeResolver::parse(repo_dir.path());
+
+        assert_eq!(scope.entries.len(), 1);
+        let repo_dir = fs::canonicalize(repo_dir.path()).unwrap();
+        let entry = scope.find(repo_dir.join("src/Vault.sol")).unwrap();
+        assert_eq!(entry.nsloc, Some(10));
+        assert!(scope.find("src/Vault.sol").is_some());
+        assert!(scope.find(repo_dir.join("src/Other.sol")).is_none());
+        // dependencies sharing a suffix with an entry are not in scope
+        assert!(scope
+            .find(repo_dir.join("lib/vault/src/Vault.sol"))
+            .is_none());
+        assert!(scope.find("/tmp/other/src/Vault.sol").is_none());
+    }
+}
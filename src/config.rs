@@ -0,0 +1,443 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use rr_logging::warn;
+use serde::Deserialize;
+
+use crate::{
+    compiler::{BuildBackend, FindContractsOptions},
+    crawler::{codearena, sherlock},
+    errors::AppError,
+    export::Exporter,
+};
+
+/// Config file read from the working directory, unless `C4C_CONFIG` or `--config` is set
+pub const CONFIG_FILE: &str = "c4c.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Settings of the tool, read from `c4c.toml` then overridden by `C4C_*` environment variables
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Log filter, `RUST_LOG` takes precedence
+    pub log: String,
+    /// Where repos are cloned, next to the contest store and the similarity index. Relative
+    /// to the working directory.
+    pub workspace_dir: PathBuf,
+    /// Raises the GitHub API rate limit, and lets private repos be seen by their members
+    pub github_token: Option<String>,
+    pub concurrency: ConcurrencyConfig,
+    pub timeouts: TimeoutsConfig,
+    pub crawlers: CrawlersConfig,
+    pub build: BuildConfig,
+    pub export: ExportConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            log: "info,headless_chrome=error".to_owned(),
+            workspace_dir: PathBuf::from("contests"),
+            github_token: None,
+            concurrency: ConcurrencyConfig::default(),
+            timeouts: TimeoutsConfig::default(),
+            crawlers: CrawlersConfig::default(),
+            build: BuildConfig::default(),
+            export: ExportConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// Platforms fetched at once
+    pub crawlers: usize,
+    /// Code4rena contest pages scraped at once
+    pub contest_pages: usize,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            crawlers: 5,
+            contest_pages: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutsConfig {
+    /// Timeout of HTTP requests, in seconds
+    pub http_secs: u64,
+    /// How long a scraped page element is waited for, in seconds
+    pub page_element_secs: u64,
+}
+
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            http_secs: 30,
+            page_element_secs: 5,
+        }
+    }
+}
+
+impl TimeoutsConfig {
+    pub fn http(&self) -> Duration {
+        Duration::from_secs(self.http_secs)
+    }
+
+    pub fn page_element(&self) -> Duration {
+        Duration::from_secs(self.page_element_secs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlersConfig {
+    pub code4rena: Code4renaConfig,
+    pub sherlock: SherlockConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Code4renaConfig {
+    pub enabled: bool,
+    /// Scraping starts a browser, poll less often
+    pub poll_interval_secs: u64,
+    pub contests_uri: String,
+    /// CSS selectors of the scraped pages, for when the site layout changes
+    pub selectors: Code4renaSelectors,
+}
+
+impl Default for Code4renaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 30 * 60,
+            contests_uri: codearena::C4_CONTEST_URI.to_owned(),
+            selectors: Code4renaSelectors::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Code4renaSelectors {
+    pub ongoing_contests: String,
+    pub upcoming_contests: String,
+    pub contest_repo_uri: String,
+    pub contest_name: String,
+    pub contest_description: String,
+    pub contest_status: String,
+    /// `<time>` elements, read from their `datetime` attribute or else their text
+    pub contest_start_date: String,
+    pub contest_end_date: String,
+    pub contest_reward: String,
+}
+
+impl Default for Code4renaSelectors {
+    fn default() -> Self {
+        Self {
+            ongoing_contests: codearena::ONGOING_CONTESTS_SELECTOR.to_owned(),
+            upcoming_contests: codearena::UPCOMING_CONTESTS_SELECTOR.to_owned(),
+            contest_repo_uri: codearena::CONTEST_REPO_URI_SELECTOR.to_owned(),
+            contest_name: codearena::CONTEST_NAME_SELECTOR.to_owned(),
+            contest_description: codearena::CONTEST_DESCRIPTION_SELECTOR.to_owned(),
+            contest_status: codearena::CONTEST_STATUS_SELECTOR.to_owned(),
+            contest_start_date: codearena::CONTEST_START_DATE_SELECTOR.to_owned(),
+            contest_end_date: codearena::CONTEST_END_DATE_SELECTOR.to_owned(),
+            contest_reward: codearena::CONTEST_REWARD_SELECTOR.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SherlockConfig {
+    pub enabled: bool,
+    /// The contests API is cheap to poll
+    pub poll_interval_secs: u64,
+    pub api_uri: String,
+}
+
+impl Default for SherlockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 5 * 60,
+            api_uri: sherlock::SHERLOCK_CONTESTS_API.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    pub backend: BuildBackend,
+    /// Locally updated copy of the bundled `advisories.json`, used instead of it
+    pub advisories_file: Option<PathBuf>,
+    /// Keep tests, scripts, mocks and libraries, instead of only the project's own sources
+    pub all_origins: bool,
+}
+
+impl BuildConfig {
+    /// Options of the contest builds
+    pub fn find_contracts_options(&self) -> FindContractsOptions {
+        let mut options = if self.all_origins {
+            FindContractsOptions::all_origins()
+        } else {
+            FindContractsOptions::default()
+        };
+        options.project_roots.backend = self.backend;
+        options.advisories_file = self.advisories_file.clone();
+        options
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Exporters run after each contest build, none by default
+    pub exporters: Vec<Exporter>,
+    /// Regenerate the Atom feed and the iCalendar of the stored contests after each crawl
+    pub feeds: bool,
+    /// Relative to the workspace directory
+    pub dir: PathBuf,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            exporters: vec![],
+            feeds: true,
+            dir: PathBuf::from("exports"),
+        }
+    }
+}
+
+impl Config {
+    /// Read `path`, or `C4C_CONFIG`, or `c4c.toml` if it exists, then apply the environment
+    pub fn load(path: Option<&Path>) -> Result<Self, AppError> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("C4C_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(CONFIG_FILE).exists() => Self::from_file(CONFIG_FILE)?,
+            None => Self::default(),
+        };
+        config.apply_env(|name| env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_file<P>(path: P) -> Result<Self, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Override the settings with the `C4C_*` variables returned by `var`
+    pub fn apply_env<F>(&mut self, var: F) -> Result<(), AppError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(log) = var("C4C_LOG") {
+            self.log = log;
+        }
+        if let Some(workspace_dir) = var("C4C_WORKSPACE_DIR") {
+            self.workspace_dir = PathBuf::from(workspace_dir);
+        }
+        if let Some(github_token) = var("C4C_GITHUB_TOKEN").or_else(|| var("GITHUB_TOKEN")) {
+            self.github_token = Some(github_token);
+        }
+        parse_var(
+            &var,
+            "C4C_CRAWLER_CONCURRENCY",
+            &mut self.concurrency.crawlers,
+        )?;
+        parse_var(
+            &var,
+            "C4C_CONTEST_PAGE_CONCURRENCY",
+            &mut self.concurrency.contest_pages,
+        )?;
+        parse_var(&var, "C4C_HTTP_TIMEOUT_SECS", &mut self.timeouts.http_secs)?;
+        parse_var(
+            &var,
+            "C4C_PAGE_ELEMENT_TIMEOUT_SECS",
+            &mut self.timeouts.page_element_secs,
+        )?;
+
+        let code4rena = &mut self.crawlers.code4rena;
+        parse_var(&var, "C4C_CODE4RENA_ENABLED", &mut code4rena.enabled)?;
+        parse_var(
+            &var,
+            "C4C_CODE4RENA_POLL_INTERVAL_SECS",
+            &mut code4rena.poll_interval_secs,
+        )?;
+        let sherlock = &mut self.crawlers.sherlock;
+        parse_var(&var, "C4C_SHERLOCK_ENABLED", &mut sherlock.enabled)?;
+        parse_var(
+            &var,
+            "C4C_SHERLOCK_POLL_INTERVAL_SECS",
+            &mut sherlock.poll_interval_secs,
+        )?;
+
+        parse_var(&var, "C4C_BUILD_BACKEND", &mut self.build.backend)?;
+        if let Some(advisories_file) = var("C4C_ADVISORIES_FILE") {
+            self.build.advisories_file = Some(PathBuf::from(advisories_file));
+        }
+        parse_var(&var, "C4C_BUILD_ALL_ORIGINS", &mut self.build.all_origins)?;
+        if let Some(exporters) = var("C4C_EXPORTERS") {
+            self.export.exporters = exporters
+                .split(',')
+                .map(str::trim)
+                .filter(|exporter| !exporter.is_empty())
+                .map(|exporter| exporter.parse().map_err(AppError::InvalidConfig))
+                .collect::<Result<_, _>>()?;
+        }
+        parse_var(&var, "C4C_EXPORT_FEEDS", &mut self.export.feeds)?;
+        if let Some(dir) = var("C4C_EXPORT_DIR") {
+            self.export.dir = PathBuf::from(dir);
+        }
+        Ok(())
+    }
+
+    /// Config of the process, set by [`Config::init`] or else loaded on first use
+    pub fn global() -> &'static Config {
+        CONFIG.get_or_init(|| {
+            Self::load(None).unwrap_or_else(|e| {
+                warn!("Load config error {e:?}, using the defaults");
+                Self::default()
+            })
+        })
+    }
+
+    /// Set the config of the process, ignored once [`Config::global`] was used
+    pub fn init(self) -> &'static Config {
+        CONFIG.get_or_init(|| self)
+    }
+
+    /// Absolute workspace directory
+    pub fn workspace_dir(&self) -> PathBuf {
+        env::current_dir()
+            .unwrap_or_default()
+            .join(&self.workspace_dir)
+    }
+
+    /// Absolute directory of the exported files
+    pub fn export_dir(&self) -> PathBuf {
+        self.workspace_dir().join(&self.export.dir)
+    }
+}
+
+fn parse_var<F, T>(var: &F, name: &str, value: &mut T) -> Result<(), AppError>
+where
+    F: Fn(&str) -> Option<String>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(raw) = var(name) {
+        *value = raw
+            .trim()
+            .parse()
+            .map_err(|e| AppError::InvalidConfig(format!("{name}={raw:?}: {e}")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::ContractOrigin;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            workspace_dir = "/var/lib/c4c"
+
+            [concurrency]
+            contest_pages = 4
+
+            [crawlers.code4rena]
+            enabled = false
+
+            [crawlers.code4rena.selectors]
+            contest_name = "h1"
+
+            [build]
+            backend = "foundry"
+
+            [export]
+            exporters = ["json"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.workspace_dir, PathBuf::from("/var/lib/c4c"));
+        assert_eq!(config.workspace_dir(), PathBuf::from("/var/lib/c4c"));
+        assert_eq!(config.concurrency.crawlers, 5);
+        assert_eq!(config.concurrency.contest_pages, 4);
+        assert!(!config.crawlers.code4rena.enabled);
+        assert_eq!(config.crawlers.code4rena.selectors.contest_name, "h1");
+        assert_eq!(
+            config.crawlers.code4rena.selectors.contest_status,
+            Code4renaSelectors::default().contest_status
+        );
+        assert!(config.crawlers.sherlock.enabled);
+        assert_eq!(config.build.backend, BuildBackend::Foundry);
+        assert_eq!(config.export.exporters, vec![Exporter::Json]);
+
+        assert!(toml::from_str::<Config>("workspace = \"contests\"").is_err());
+    }
+
+    #[test]
+    fn test_config_apply_env() {
+        let vars: HashMap<&str, &str> = HashMap::from([
+            ("C4C_SHERLOCK_ENABLED", "false"),
+            ("C4C_CRAWLER_CONCURRENCY", "2"),
+            ("GITHUB_TOKEN", "ghp_token"),
+            ("C4C_BUILD_BACKEND", "Foundry"),
+            ("C4C_ADVISORIES_FILE", "advisories.json"),
+            ("C4C_BUILD_ALL_ORIGINS", "true"),
+            ("C4C_EXPORTERS", "json, "),
+            ("C4C_EXPORT_FEEDS", "false"),
+        ]);
+        let mut config = Config::default();
+        config
+            .apply_env(|name| vars.get(name).map(|value| value.to_string()))
+            .unwrap();
+
+        assert!(!config.crawlers.sherlock.enabled);
+        assert!(config.crawlers.code4rena.enabled);
+        assert_eq!(config.concurrency.crawlers, 2);
+        assert_eq!(config.github_token.as_deref(), Some("ghp_token"));
+        assert_eq!(config.build.backend, BuildBackend::Foundry);
+        assert_eq!(
+            config.build.find_contracts_options().advisories_file,
+            Some(PathBuf::from("advisories.json"))
+        );
+        assert!(config.build.find_contracts_options().origins.is_empty());
+        assert_eq!(
+            Config::default().build.find_contracts_options().origins,
+            vec![ContractOrigin::Source]
+        );
+        assert_eq!(config.export.exporters, vec![Exporter::Json]);
+        assert!(!config.export.feeds);
+
+        let result = Config::default()
+            .apply_env(|name| (name == "C4C_HTTP_TIMEOUT_SECS").then(|| "soon".to_owned()));
+        assert!(matches!(result, Err(AppError::InvalidConfig(_))));
+    }
+}
@@ -1,5 +1,6 @@
+use crate::config::Config;
 use crate::errors::AppError;
-use crate::types::{Contest, ContestStatus};
+use crate::types::{Contest, ContestPlatform, ContestStatus};
 use futures::stream::StreamExt;
 use headless_chrome::{Browser, Element, Tab};
 use lazy_static::lazy_static;
@@ -16,31 +17,43 @@ pub mod sherlock;
 pub mod utils;
 
 #[async_trait::async_trait]
-pub trait ContestCrawler {
+pub trait ContestCrawler: Send + Sync {
+    fn platform(&self) -> ContestPlatform;
+
+    /// How often the platform is polled in watch mode
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
     async fn fetch_all_contests(&self) -> Result<Vec<Contest>, AppError>;
 }
 
-pub fn get_crawlers() -> Vec<Arc<dyn ContestCrawler>> {
+/// Crawlers of the platforms enabled in `config`
+pub fn get_crawlers(config: &Config) -> Vec<Arc<dyn ContestCrawler>> {
     let mut crawlers: Vec<Arc<dyn ContestCrawler>> = vec![];
     // C4
-    let codearena_crawler = CodearenaCrawler::default();
-    crawlers.push(Arc::new(codearena_crawler));
+    if config.crawlers.code4rena.enabled {
+        let codearena_crawler = CodearenaCrawler::new(config);
+        crawlers.push(Arc::new(codearena_crawler));
+    }
 
     // sherlock
-    let sherlock_crawler = SherlockCrawler::default();
-    crawlers.push(Arc::new(sherlock_crawler));
+    if config.crawlers.sherlock.enabled {
+        let sherlock_crawler = SherlockCrawler::new(config);
+        crawlers.push(Arc::new(sherlock_crawler));
+    }
 
     crawlers
 }
 
 /// Fetch all contests from Code4rena, Sherlock, Immunefi, Blackhat
-#[instrument]
-pub async fn fetch_all_contests() -> Result<Vec<Contest>, AppError> {
-    let crawlers = get_crawlers();
+#[instrument(skip_all)]
+pub async fn fetch_all_contests(config: &Config) -> Result<Vec<Contest>, AppError> {
+    let crawlers = get_crawlers(config);
 
     let tasks = crawlers.iter().map(|crawler| crawler.fetch_all_contests());
 
-    let mut stream = futures::stream::iter(tasks).buffered(5);
+    let mut stream = futures::stream::iter(tasks).buffered(config.concurrency.crawlers.max(1));
 
     let mut result = vec![];
     while let Some(response) = stream.next().await {
@@ -49,21 +62,49 @@ pub async fn fetch_all_contests() -> Result<Vec<Contest>, AppError> {
             continue;
         }
 
-        // filter out private contests
-        let contests = response.unwrap();
-        for contest in contests {
-            if contest.repo_uri.is_none() {
-                warn!("Skipping private contest {:#?}", contest.name);
-                continue;
-            }
+        result.extend(hide_private_repos(response.unwrap()).await);
+    }
+    Ok(result)
+}
 
-            let repo_uri = contest.repo_uri.as_ref().unwrap();
-            if is_repo_private(&repo_uri).await.unwrap_or(true) {
-                warn!("Skipping private contest {:#?}", contest.name);
-                continue;
+/// Remove the repo of private contests. They are kept to notice once their repo is published.
+pub async fn hide_private_repos(contests: Vec<Contest>) -> Vec<Contest> {
+    let mut result = vec![];
+    for mut contest in contests {
+        if let Some(repo_uri) = contest.repo_uri.as_ref() {
+            if is_repo_private(repo_uri).await.unwrap_or(true) {
+                contest.repo_uri = None;
             }
-            result.push(contest);
         }
+        if contest.repo_uri.is_none() {
+            warn!("Repo of contest {:#?} is private", contest.name);
+        }
+        result.push(contest);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_crawlers() {
+        let mut config = Config::default();
+        let platforms: Vec<ContestPlatform> = get_crawlers(&config)
+            .iter()
+            .map(|crawler| crawler.platform())
+            .collect();
+        assert_eq!(
+            platforms,
+            vec![ContestPlatform::Code4rena, ContestPlatform::Sherlock]
+        );
+
+        config.crawlers.code4rena.enabled = false;
+        config.crawlers.sherlock.poll_interval_secs = 60;
+        let crawlers = get_crawlers(&config);
+        assert_eq!(crawlers.len(), 1);
+        assert_eq!(crawlers[0].platform(), ContestPlatform::Sherlock);
+        assert_eq!(crawlers[0].poll_interval(), Duration::from_secs(60));
     }
-    Ok(result)
 }
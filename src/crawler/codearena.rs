@@ -1,3 +1,4 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use futures::stream::StreamExt;
 use rr_logging::{info, instrument, tracing};
 use std::{sync::Arc, time::Duration};
@@ -5,31 +6,64 @@ use std::{sync::Arc, time::Duration};
 use headless_chrome::{Browser, Element, Tab};
 
 use crate::{
+    config::{Code4renaConfig, Code4renaSelectors, Config},
     errors::AppError,
-    types::{Contest, ContestStatus},
+    types::{Contest, ContestPlatform, ContestStatus},
 };
 
 use super::ContestCrawler;
 
-const ONGOING_CONTESTS_SELECTOR: &'static str =
+pub const ONGOING_CONTESTS_SELECTOR: &'static str =
     "body > div.wrapper__grid > main > div > div > section:nth-child(1) > div > div > div > header > div.contest-tile__details-wrapper > h2 > a";
-const UPCOMING_CONTESTS_SELECTOR: &'static str =
+pub const UPCOMING_CONTESTS_SELECTOR: &'static str =
     "body > div.wrapper__grid > main > div > div > section:nth-child(2) > div > div > div > header > div.contest-tile__details-wrapper > h2 > a";
 
-const CONTEST_REPO_URI_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__button-wrapper > a";
-const CONTEST_NAME_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__project > div:nth-child(2) > h1";
-const CONTEST_DESCRIPTION_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__project > div:nth-child(2) > p";
-const CONTEST_STATUS_SELECTOR: &'static str =
+pub const CONTEST_REPO_URI_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__button-wrapper > a";
+pub const CONTEST_NAME_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__project > div:nth-child(2) > h1";
+pub const CONTEST_DESCRIPTION_SELECTOR: &'static str  = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__project > div:nth-child(2) > p";
+pub const CONTEST_STATUS_SELECTOR: &'static str =
     "body > div.wrapper__grid > main > div > section > div.contest-page__status-bar > div > span";
+pub const CONTEST_START_DATE_SELECTOR: &'static str = "body > div.wrapper__grid > main > div > section > div.contest-page__status-bar > div.contest-page__dates > time:nth-of-type(1)";
+pub const CONTEST_END_DATE_SELECTOR: &'static str = "body > div.wrapper__grid > main > div > section > div.contest-page__status-bar > div.contest-page__dates > time:nth-of-type(2)";
+pub const CONTEST_REWARD_SELECTOR: &'static str = "body > div.wrapper__grid > main > div > section > div.contest-page__top-content > div.contest-page__project > div:nth-child(2) > div.contest-page__prize";
 
 pub const C4_URI: &'static str = "https://code4rena.com";
 pub const C4_CONTEST_URI: &'static str = "https://code4rena.com/contests";
 
-#[derive(Debug, Default)]
-pub struct CodearenaCrawler {}
+#[derive(Debug, Clone)]
+pub struct CodearenaCrawler {
+    config: Code4renaConfig,
+    /// Contest pages scraped at once
+    contest_pages: usize,
+    page_element_timeout: Duration,
+}
+
+impl CodearenaCrawler {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.crawlers.code4rena.clone(),
+            contest_pages: config.concurrency.contest_pages.max(1),
+            page_element_timeout: config.timeouts.page_element(),
+        }
+    }
+}
+
+impl Default for CodearenaCrawler {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
+}
 
 #[async_trait::async_trait]
 impl ContestCrawler for CodearenaCrawler {
+    fn platform(&self) -> ContestPlatform {
+        ContestPlatform::Code4rena
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.config.poll_interval_secs)
+    }
+
     #[instrument(skip_all)]
     async fn fetch_all_contests(&self) -> Result<Vec<Contest>, AppError> {
         let mut result: Vec<Contest> = vec![];
@@ -38,10 +72,11 @@ impl ContestCrawler for CodearenaCrawler {
 
         let tab = browser.new_tab()?;
 
-        tab.navigate_to(C4_CONTEST_URI)?;
+        tab.navigate_to(&self.config.contests_uri)?;
 
-        let mut ongoing_contests = tab.wait_for_elements(ONGOING_CONTESTS_SELECTOR)?;
-        let upcoming_contests = tab.wait_for_elements(UPCOMING_CONTESTS_SELECTOR)?;
+        let selectors = &self.config.selectors;
+        let mut ongoing_contests = tab.wait_for_elements(&selectors.ongoing_contests)?;
+        let upcoming_contests = tab.wait_for_elements(&selectors.upcoming_contests)?;
 
         info!(
             "Got {:#?} Code4rena contests. Ongoing contests = {:#?}. Upcoming contests = {:#?}",
@@ -67,9 +102,14 @@ impl ContestCrawler for CodearenaCrawler {
         let mut stream_result = futures::stream::iter(contests_uri)
             .map(|contest_uri| {
                 let tab = browser.new_tab().expect("Failed to spawn new tab");
-                tokio::spawn(extract_contest(tab, contest_uri))
+                tokio::spawn(extract_contest(
+                    tab,
+                    contest_uri,
+                    selectors.clone(),
+                    self.page_element_timeout,
+                ))
             })
-            .buffer_unordered(10); // allow buffer 10 items
+            .buffer_unordered(self.contest_pages);
 
         while let Some(s) = stream_result.next().await {
             // extract tokio join error
@@ -87,22 +127,24 @@ impl ContestCrawler for CodearenaCrawler {
 async fn extract_contest<'a>(
     tab: Arc<Tab>,
     contest_detail_uri: String,
+    selectors: Code4renaSelectors,
+    timeout: Duration,
 ) -> Result<Contest, AppError> {
     tab.navigate_to(&contest_detail_uri)?;
 
     // get contest name
     let name = tab
-        .wait_for_element(CONTEST_NAME_SELECTOR)?
+        .wait_for_element(&selectors.contest_name)?
         .get_inner_text()?;
 
     // get contest description
     let description = tab
-        .wait_for_element(CONTEST_DESCRIPTION_SELECTOR)?
+        .wait_for_element(&selectors.contest_description)?
         .get_inner_text()?;
 
     let mut contest_repo_uri: Option<String> = None;
     if let Ok(repo_uri) =
-        tab.wait_for_element_with_custom_timeout(CONTEST_REPO_URI_SELECTOR, Duration::from_secs(5))
+        tab.wait_for_element_with_custom_timeout(&selectors.contest_repo_uri, timeout)
     {
         contest_repo_uri = get_element_single_attribute(&repo_uri, "href");
     }
@@ -110,22 +152,70 @@ async fn extract_contest<'a>(
     let mut contest_status: ContestStatus = ContestStatus::Upcoming;
 
     let status = tab
-        .wait_for_element_with_custom_timeout(CONTEST_STATUS_SELECTOR, Duration::from_secs(5))?
+        .wait_for_element_with_custom_timeout(&selectors.contest_status, timeout)?
         .get_inner_text()?;
     if status.contains("Live") {
         contest_status = ContestStatus::Ongoing;
     }
 
+    let start_date = tab
+        .wait_for_element_with_custom_timeout(&selectors.contest_start_date, timeout)
+        .ok()
+        .and_then(|element| get_element_date(&element));
+    let end_date = tab
+        .wait_for_element_with_custom_timeout(&selectors.contest_end_date, timeout)
+        .ok()
+        .and_then(|element| get_element_date(&element));
+    let reward = tab
+        .wait_for_element_with_custom_timeout(&selectors.contest_reward, timeout)
+        .ok()
+        .and_then(|element| element.get_inner_text().ok())
+        .map(|reward| reward.trim().to_owned())
+        .filter(|reward| !reward.is_empty());
+
+    // contest pages are `https://code4rena.com/contests/<slug>`
+    let id = contest_detail_uri
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+
     Ok(Contest {
+        platform: ContestPlatform::Code4rena,
+        id,
         name,
         description,
         uri: contest_detail_uri,
         repo_uri: contest_repo_uri,
         status: contest_status,
+        start_date,
+        end_date,
+        reward,
         contracts: vec![],
     })
 }
 
+fn get_element_date(element: &Element) -> Option<DateTime<Utc>> {
+    get_element_single_attribute(element, "datetime")
+        .or_else(|| element.get_inner_text().ok())
+        .and_then(|text| parse_contest_date(&text))
+}
+
+/// Parse a contest date as shown by Code4rena, `2023-05-30T20:00:00Z` or
+/// `May 30, 2023 20:00 UTC`
+pub fn parse_contest_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    let text = text.trim_end_matches("UTC").trim();
+    ["%B %d, %Y %H:%M", "%b %d, %Y %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|date| Utc.from_utc_datetime(&date))
+}
+
 fn get_element_single_attribute<'a>(element: &'a Element, attribute: &str) -> Option<String> {
     if let Ok(attributes) = element.get_attributes() {
         if let Some(attributes) = attributes {
@@ -138,3 +228,19 @@ fn get_element_single_attribute<'a>(element: &'a Element, attribute: &str) -> Op
     }
     return None;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_contest_date() {
+        let date = Utc.with_ymd_and_hms(2023, 5, 30, 20, 0, 0).single();
+
+        assert_eq!(parse_contest_date("2023-05-30T20:00:00Z"), date);
+        assert_eq!(parse_contest_date("2023-05-30T22:00:00+02:00"), date);
+        assert_eq!(parse_contest_date(" May 30, 2023 20:00 UTC "), date);
+        assert!(parse_contest_date("Sep 5, 2023 20:00 UTC").is_some());
+        assert_eq!(parse_contest_date("Live"), None);
+    }
+}
@@ -1,15 +1,18 @@
+use chrono::{DateTime, TimeZone, Utc};
 use rr_logging::{info, instrument, tracing};
 use serde::Deserialize;
+use std::time::Duration;
 
 use crate::{
+    config::{Config, SherlockConfig},
     errors::AppError,
-    types::{Contest, ContestStatus},
+    types::{Contest, ContestPlatform, ContestStatus},
 };
 
 use super::ContestCrawler;
 
 const SHERLOCK_CONTESTS_URI: &'static str = "https://app.sherlock.xyz/audits/contests";
-const SHERLOCK_CONTESTS_API: &'static str = "https://mainnet-contest.sherlock.xyz/contests";
+pub const SHERLOCK_CONTESTS_API: &'static str = "https://mainnet-contest.sherlock.xyz/contests";
 
 #[derive(Debug, Deserialize)]
 struct SherlockContestApiResponse {
@@ -19,6 +22,10 @@ struct SherlockContestApiResponse {
     title: String,
     short_description: String,
     private: bool,
+    /// Unix timestamps
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+    prize_pool: Option<u64>,
 }
 
 #[allow(non_camel_case_types)]
@@ -38,10 +45,29 @@ impl Status {
     }
 }
 
-#[derive(Debug, Default)]
-pub struct SherlockCrawler {}
+#[derive(Debug, Clone)]
+pub struct SherlockCrawler {
+    config: SherlockConfig,
+    client: reqwest::Client,
+}
+
+impl Default for SherlockCrawler {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
+}
 
 impl SherlockCrawler {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.crawlers.sherlock.clone(),
+            client: reqwest::Client::builder()
+                .timeout(config.timeouts.http())
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
     fn contest_uri(contest_id: usize) -> String {
         format!("{}/{contest_id:}", SHERLOCK_CONTESTS_URI)
     }
@@ -50,6 +76,10 @@ impl SherlockCrawler {
         format!("https://github.com/{template_repo_name:}")
     }
 
+    fn date(timestamp: i64) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(timestamp, 0).single()
+    }
+
     fn contest_status(status: &Status) -> ContestStatus {
         match status {
             Status::CREATED => ContestStatus::Upcoming,
@@ -61,10 +91,23 @@ impl SherlockCrawler {
 
 #[async_trait::async_trait]
 impl ContestCrawler for SherlockCrawler {
+    fn platform(&self) -> ContestPlatform {
+        ContestPlatform::Sherlock
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.config.poll_interval_secs)
+    }
+
     #[instrument(skip_all)]
     async fn fetch_all_contests(&self) -> Result<Vec<Contest>, AppError> {
-        let response: Vec<SherlockContestApiResponse> =
-            reqwest::get(SHERLOCK_CONTESTS_API).await?.json().await?;
+        let response: Vec<SherlockContestApiResponse> = self
+            .client
+            .get(&self.config.api_uri)
+            .send()
+            .await?
+            .json()
+            .await?;
 
         let result = response
             .iter()
@@ -74,11 +117,16 @@ impl ContestCrawler for SherlockCrawler {
                 }
 
                 Some(Contest {
+                    platform: ContestPlatform::Sherlock,
+                    id: item.id.to_string(),
                     name: item.title.to_owned(),
                     description: item.short_description.to_owned(),
                     uri: SherlockCrawler::contest_uri(item.id),
                     repo_uri: Some(SherlockCrawler::repo_uri(&item.template_repo_name)),
                     status: SherlockCrawler::contest_status(&item.status),
+                    start_date: item.starts_at.and_then(SherlockCrawler::date),
+                    end_date: item.ends_at.and_then(SherlockCrawler::date),
+                    reward: item.prize_pool.map(|prize| format!("${prize} USDC")),
                     contracts: vec![],
                 })
             })
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::errors::AppError;
+use crate::{config::Config, errors::AppError};
 
 #[derive(Debug, Deserialize)]
 struct GithubRepoApiResponse {
@@ -11,7 +11,10 @@ pub async fn is_repo_private(repo_uri: &str) -> Result<bool, AppError> {
     let repo_name = repo_uri.replace("https://github.com/", "");
     let repo_api = format!("https://api.github.com/repos/{}", repo_name);
 
-    let client = reqwest::Client::new();
+    let config = Config::global();
+    let client = reqwest::Client::builder()
+        .timeout(config.timeouts.http())
+        .build()?;
     // let text = client
     //     .get(&repo_api)
     //     .header("User-Agent", "quangkeu95")
@@ -21,14 +24,12 @@ pub async fn is_repo_private(repo_uri: &str) -> Result<bool, AppError> {
     //     .await?;
     // println!("{:?}", text);
 
-    if let Ok(_res) = client
-        .get(&repo_api)
-        .header("User-Agent", "quangkeu95")
-        .send()
-        .await?
-        .json::<GithubRepoApiResponse>()
-        .await
-    {
+    let mut request = client.get(&repo_api).header("User-Agent", "quangkeu95");
+    if let Some(github_token) = config.github_token.as_ref() {
+        request = request.bearer_auth(github_token);
+    }
+
+    if let Ok(_res) = request.send().await?.json::<GithubRepoApiResponse>().await {
         return Ok(false);
     } else {
         return Ok(true);
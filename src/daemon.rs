@@ -0,0 +1,204 @@
+use std::{sync::Arc, time::Duration};
+
+use rand::Rng;
+use rr_logging::{error, info, warn};
+use tokio::{
+    runtime::Handle,
+    signal::unix::{signal, SignalKind},
+    task,
+    time::{sleep_until, Instant},
+};
+
+use crate::{
+    crawler::ContestCrawler, errors::AppError, events::ContestEvent, pipeline::ContestPipeline,
+};
+
+/// Options of [`Daemon`]
+#[derive(Debug, Clone)]
+pub struct DaemonConfig {
+    /// How often the repos of ongoing contests are checked for new commits
+    pub repo_refresh_interval: Duration,
+    /// Random share of an interval added or removed, so that polls don't align
+    pub jitter: f64,
+    /// Upper bound of the delay after repeated errors
+    pub max_backoff: Duration,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            repo_refresh_interval: Duration::from_secs(10 * 60),
+            jitter: 0.1,
+            max_backoff: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+/// Poll every crawler on its own interval until SIGTERM or Ctrl-C. Jobs run on a blocking
+/// thread since builds clone and compile synchronously, a signal received meanwhile stops the
+/// daemon without waiting for the build.
+pub struct Daemon {
+    pipeline: ContestPipeline,
+    crawlers: Vec<Arc<dyn ContestCrawler>>,
+    config: DaemonConfig,
+}
+
+/// A recurring job of the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Job {
+    Crawl(usize),
+    RefreshRepos,
+}
+
+impl Daemon {
+    pub fn new(
+        pipeline: ContestPipeline,
+        crawlers: Vec<Arc<dyn ContestCrawler>>,
+        config: DaemonConfig,
+    ) -> Self {
+        Self {
+            pipeline,
+            crawlers,
+            config,
+        }
+    }
+
+    pub async fn run(self) -> Result<(), AppError> {
+        let Self {
+            mut pipeline,
+            crawlers,
+            config,
+        } = self;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+
+        let mut jobs: Vec<Job> = (0..crawlers.len()).map(Job::Crawl).collect();
+        jobs.push(Job::RefreshRepos);
+        // every job runs on start, then on its own schedule
+        let mut next_runs: Vec<Instant> = vec![Instant::now(); jobs.len()];
+        let mut failures: Vec<u32> = vec![0; jobs.len()];
+
+        loop {
+            let (index, next_run) = next_runs
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by_key(|(_, next_run)| *next_run)
+                .expect("at least the repo refresh job");
+
+            tokio::select! {
+                _ = sleep_until(next_run) => {}
+                _ = terminate.recv() => break,
+                _ = interrupt.recv() => break,
+            }
+
+            let job = jobs[index];
+            let handle = Handle::current();
+            let job_crawlers = crawlers.clone();
+            let mut running = task::spawn_blocking(move || {
+                let result = handle.block_on(run_job(&mut pipeline, &job_crawlers, job));
+                (pipeline, result)
+            });
+            let result = tokio::select! {
+                joined = &mut running => {
+                    let (returned, result) = joined?;
+                    pipeline = returned;
+                    result
+                }
+                _ = terminate.recv() => {
+                    warn!("Shutting down during {job:?}, abandoning it");
+                    break;
+                }
+                _ = interrupt.recv() => {
+                    warn!("Shutting down during {job:?}, abandoning it");
+                    break;
+                }
+            };
+            match result {
+                Ok(events) => {
+                    info!("{job:?} done with {} events", events.len());
+                    failures[index] = 0;
+                }
+                Err(e) => {
+                    error!("{job:?} error {e:?}");
+                    failures[index] += 1;
+                }
+            }
+
+            let interval = match job {
+                Job::Crawl(crawler) => crawlers[crawler].poll_interval(),
+                Job::RefreshRepos => config.repo_refresh_interval,
+            };
+            let delay = with_jitter(
+                backoff(interval, failures[index], config.max_backoff),
+                config.jitter,
+            );
+            if failures[index] > 0 {
+                warn!("Retrying {job:?} in {delay:?}");
+            }
+            next_runs[index] = Instant::now() + delay;
+        }
+
+        info!("Shutting down");
+        Ok(())
+    }
+}
+
+async fn run_job(
+    pipeline: &mut ContestPipeline,
+    crawlers: &[Arc<dyn ContestCrawler>],
+    job: Job,
+) -> Result<Vec<ContestEvent>, AppError> {
+    match job {
+        Job::Crawl(crawler) => pipeline.crawl(crawlers[crawler].as_ref()).await,
+        Job::RefreshRepos => pipeline.refresh_ongoing().await,
+    }
+}
+
+/// `interval` doubled on every consecutive failure, up to `max_backoff`
+pub fn backoff(interval: Duration, failures: u32, max_backoff: Duration) -> Duration {
+    if failures == 0 {
+        return interval;
+    }
+    interval
+        .checked_mul(2u32.saturating_pow(failures.min(16)))
+        .unwrap_or(max_backoff)
+        .min(max_backoff.max(interval))
+}
+
+/// `delay` plus or minus a random share of up to `jitter`
+pub fn with_jitter(delay: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return delay;
+    }
+    let factor = rand::thread_rng().gen_range(-jitter..=jitter);
+    delay.mul_f64((1.0 + factor).max(0.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let minute = Duration::from_secs(60);
+        let hour = Duration::from_secs(60 * 60);
+
+        assert_eq!(backoff(minute, 0, hour), minute);
+        assert_eq!(backoff(minute, 1, hour), 2 * minute);
+        assert_eq!(backoff(minute, 3, hour), 8 * minute);
+        assert_eq!(backoff(minute, 10, hour), hour);
+        assert_eq!(backoff(minute, u32::MAX, hour), hour);
+    }
+
+    #[test]
+    fn test_with_jitter() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(with_jitter(minute, 0.0), minute);
+        for _ in 0..100 {
+            let delay = with_jitter(minute, 0.1);
+            assert!(delay >= Duration::from_secs(54) && delay <= Duration::from_secs(66));
+        }
+    }
+}
@@ -16,6 +16,12 @@ pub enum AppError {
     ParseFoundryConfigError(String),
     #[error("Parse Hardhat config error {0:#?}")]
     ParseHardhatConfigError(String),
+    #[error("Invalid ignore glob {0:#?}")]
+    InvalidIgnoreGlob(String),
+    #[error("Invalid config {0:#?}")]
+    InvalidConfig(String),
+    #[error("Invalid repo uri {0:#?}")]
+    InvalidRepoUri(String),
     #[error(transparent)]
     TokioJoinHandleError(#[from] tokio::task::JoinError),
     #[error(transparent)]
@@ -29,6 +35,18 @@ pub enum AppError {
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
     #[error(transparent)]
+    SmtpError(#[from] lettre::transport::smtp::Error),
+    #[error(transparent)]
+    EmailError(#[from] lettre::error::Error),
+    #[error(transparent)]
+    EmailAddressError(#[from] lettre::address::AddressError),
+    #[error(transparent)]
+    SqliteError(#[from] rusqlite::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    TomlError(#[from] toml::de::Error),
+    #[error(transparent)]
     StdIoError(#[from] std::io::Error),
     #[error(transparent)]
     UnknownError(#[from] anyhow::Error),
@@ -0,0 +1,227 @@
+use std::fmt::Display;
+
+use crate::{
+    store::StoredContest,
+    types::{Contest, ContestPlatform, ContestStatus},
+};
+
+/// A contest along with the commit its repo was at when it was fetched
+#[derive(Debug, Clone)]
+pub struct ContestSnapshot {
+    pub contest: Contest,
+    pub repo_commit: Option<String>,
+}
+
+impl From<StoredContest> for ContestSnapshot {
+    fn from(stored: StoredContest) -> Self {
+        Self {
+            contest: stored.contest,
+            repo_commit: stored.repo_commit,
+        }
+    }
+}
+
+/// A change of a contest between two runs
+#[derive(Debug, Clone)]
+pub enum ContestEvent {
+    ContestAdded(Contest),
+    /// The contest went from upcoming to ongoing
+    ContestStarted(Contest),
+    /// The repo of a contest seen before became public
+    RepoPublished(Contest),
+    RepoUpdated {
+        contest: Contest,
+        previous_commit: String,
+        commit: String,
+    },
+    /// The contest is no longer listed by its platform
+    ContestEnded(Contest),
+}
+
+impl ContestEvent {
+    pub fn contest(&self) -> &Contest {
+        match self {
+            Self::ContestAdded(contest)
+            | Self::ContestStarted(contest)
+            | Self::RepoPublished(contest)
+            | Self::RepoUpdated { contest, .. }
+            | Self::ContestEnded(contest) => contest,
+        }
+    }
+
+    /// Name of the event, e.g. `contest_added`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ContestAdded(_) => "contest_added",
+            Self::ContestStarted(_) => "contest_started",
+            Self::RepoPublished(_) => "repo_published",
+            Self::RepoUpdated { .. } => "repo_updated",
+            Self::ContestEnded(_) => "contest_ended",
+        }
+    }
+
+    /// Whether the contest repo should be built again
+    pub fn requires_build(&self) -> bool {
+        match self {
+            Self::ContestAdded(contest) => contest.repo_uri.is_some(),
+            Self::RepoPublished(_) | Self::RepoUpdated { .. } => true,
+            Self::ContestStarted(_) | Self::ContestEnded(_) => false,
+        }
+    }
+}
+
+impl Display for ContestEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let contest = self.contest();
+        match self {
+            Self::ContestAdded(_) => write!(f, "New contest {}", contest.name),
+            Self::ContestStarted(_) => write!(f, "Contest {} started", contest.name),
+            Self::RepoPublished(_) => write!(f, "Repo of contest {} published", contest.name),
+            Self::RepoUpdated {
+                previous_commit,
+                commit,
+                ..
+            } => write!(
+                f,
+                "Repo of contest {} updated from {} to {}",
+                contest.name,
+                short_commit(previous_commit),
+                short_commit(commit)
+            ),
+            Self::ContestEnded(_) => write!(f, "Contest {} ended", contest.name),
+        }?;
+        write!(f, " ({})", contest.uri)
+    }
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+/// Compare fetched contests with the previous snapshot
+pub struct ContestEventResolver {}
+
+impl ContestEventResolver {
+    /// Events turning `previous` into `current`. Only contests of the `fetched` platforms can end,
+    /// so that a failing crawler doesn't end all the contests of its platform.
+    pub fn diff(
+        previous: &[ContestSnapshot],
+        current: &[ContestSnapshot],
+        fetched: &[ContestPlatform],
+    ) -> Vec<ContestEvent> {
+        let mut events: Vec<ContestEvent> = vec![];
+
+        for snapshot in current.iter() {
+            let contest = &snapshot.contest;
+            let Some(before) = previous
+                .iter()
+                .find(|before| is_same(&before.contest, contest))
+            else {
+                events.push(ContestEvent::ContestAdded(contest.clone()));
+                continue;
+            };
+
+            if before.contest.status == ContestStatus::Upcoming
+                && contest.status == ContestStatus::Ongoing
+            {
+                events.push(ContestEvent::ContestStarted(contest.clone()));
+            }
+            if before.contest.repo_uri.is_none() && contest.repo_uri.is_some() {
+                events.push(ContestEvent::RepoPublished(contest.clone()));
+            } else if let (Some(previous_commit), Some(commit)) =
+                (&before.repo_commit, &snapshot.repo_commit)
+            {
+                if previous_commit != commit {
+                    events.push(ContestEvent::RepoUpdated {
+                        contest: contest.clone(),
+                        previous_commit: previous_commit.clone(),
+                        commit: commit.clone(),
+                    });
+                }
+            }
+        }
+
+        for before in previous.iter() {
+            let contest = &before.contest;
+            if contest.status != ContestStatus::Ended
+                && fetched.contains(&contest.platform)
+                && !current
+                    .iter()
+                    .any(|snapshot| is_same(&snapshot.contest, contest))
+            {
+                events.push(ContestEvent::ContestEnded(contest.clone()));
+            }
+        }
+
+        events
+    }
+}
+
+fn is_same(a: &Contest, b: &Contest) -> bool {
+    a.platform == b.platform && a.id == b.id
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(
+        platform: ContestPlatform,
+        id: &str,
+        status: ContestStatus,
+        repo_commit: Option<&str>,
+    ) -> ContestSnapshot {
+        ContestSnapshot {
+            contest: Contest {
+                platform,
+                id: id.to_owned(),
+                name: id.to_owned(),
+                description: String::new(),
+                uri: format!("https://code4rena.com/contests/{id}"),
+                repo_uri: repo_commit.map(|_| format!("https://github.com/code-423n4/{id}")),
+                status,
+                start_date: None,
+                end_date: None,
+                reward: None,
+                contracts: vec![],
+            },
+            repo_commit: repo_commit.map(|commit| commit.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_contest_events() {
+        use ContestPlatform::*;
+        use ContestStatus::*;
+
+        let previous = vec![
+            snapshot(Code4rena, "maia", Upcoming, None),
+            snapshot(Code4rena, "lybra", Ongoing, Some("1111111aaaa")),
+            snapshot(Code4rena, "dodo", Ongoing, Some("2222222bbbb")),
+            snapshot(Code4rena, "ended", Ended, Some("3333333cccc")),
+            // no Sherlock contest was fetched this time
+            snapshot(Sherlock, "93", Ongoing, Some("4444444dddd")),
+        ];
+        let current = vec![
+            snapshot(Code4rena, "maia", Ongoing, Some("5555555eeee")),
+            snapshot(Code4rena, "lybra", Ongoing, Some("6666666ffff")),
+            snapshot(Code4rena, "chainlink", Upcoming, None),
+        ];
+
+        let events: Vec<String> = ContestEventResolver::diff(&previous, &current, &[Code4rena])
+            .iter()
+            .map(|event| event.to_string())
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                "Contest maia started (https://code4rena.com/contests/maia)",
+                "Repo of contest maia published (https://code4rena.com/contests/maia)",
+                "Repo of contest lybra updated from 1111111 to 6666666 (https://code4rena.com/contests/lybra)",
+                "New contest chainlink (https://code4rena.com/contests/chainlink)",
+                "Contest dodo ended (https://code4rena.com/contests/dodo)",
+            ]
+        );
+    }
+}
@@ -1,9 +1,123 @@
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use crate::types::Contract;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
-pub fn export_contracts_to_file<P>(repo_name: &str, contracts: Vec<Contract>, output_filepath: P)
+use crate::{
+    analyzer::SelectorTable,
+    errors::AppError,
+    types::{Contest, Contract},
+};
+
+mod calendar;
+pub use calendar::*;
+mod feed;
+pub use feed::*;
+
+/// File name of the Atom feed in the export directory
+pub const CONTESTS_FEED_FILE: &str = "contests.atom";
+/// File name of the iCalendar in the export directory
+pub const CONTESTS_CALENDAR_FILE: &str = "contests.ics";
+
+#[derive(Debug, Serialize)]
+struct ExportedRepo<'a> {
+    repo: &'a str,
+    contracts: Vec<ExportedContract<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedContract<'a> {
+    name: &'a str,
+    contract_type: String,
+    version: String,
+    source_file: &'a Path,
+    in_scope: bool,
+    selectors: Option<&'a SelectorTable>,
+}
+
+impl<'a> From<&'a Contract> for ExportedContract<'a> {
+    fn from(contract: &'a Contract) -> Self {
+        Self {
+            name: &contract.name,
+            contract_type: format!("{:?}", contract.contract_type),
+            version: contract.version.to_string(),
+            source_file: &contract.source_file,
+            in_scope: contract.in_scope,
+            selectors: contract.selectors.as_ref(),
+        }
+    }
+}
+
+/// Write the contracts of a repo as JSON
+pub fn export_contracts_to_file<P>(
+    repo_name: &str,
+    contracts: &[Contract],
+    output_filepath: P,
+) -> Result<(), AppError>
 where
-    P: AsRef<PathBuf>,
+    P: AsRef<Path>,
 {
+    let exported = ExportedRepo {
+        repo: repo_name,
+        contracts: contracts.iter().map(ExportedContract::from).collect(),
+    };
+    let content = serde_json::to_string_pretty(&exported)?;
+    fs::write(output_filepath.as_ref(), content)?;
+    Ok(())
+}
+
+/// Write the Atom feed and the iCalendar of `contests` into `output_dir`, returns the written files
+pub fn export_contest_feeds(
+    contests: &[Contest],
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>, AppError> {
+    fs::create_dir_all(output_dir)?;
+    let now = Utc::now();
+    let feed_file = output_dir.join(CONTESTS_FEED_FILE);
+    fs::write(&feed_file, contests_to_atom(contests, now))?;
+    let calendar_file = output_dir.join(CONTESTS_CALENDAR_FILE);
+    fs::write(&calendar_file, contests_to_ics(contests, now))?;
+    Ok(vec![feed_file, calendar_file])
+}
+
+/// Format the contracts of a built contest are exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Exporter {
+    /// See [`export_contracts_to_file`]
+    Json,
+}
+
+impl Exporter {
+    /// Export the contracts of a repo into `output_dir`, returns the written file
+    pub fn export(
+        &self,
+        repo_name: &str,
+        contracts: &[Contract],
+        output_dir: &Path,
+    ) -> Result<PathBuf, AppError> {
+        fs::create_dir_all(output_dir)?;
+        match self {
+            Self::Json => {
+                let output_file = output_dir.join(format!("{repo_name}.json"));
+                export_contracts_to_file(repo_name, contracts, &output_file)?;
+                Ok(output_file)
+            }
+        }
+    }
+}
+
+impl FromStr for Exporter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown exporter {s:?}")),
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    notifier::ContestMessage,
+    types::{Contest, DateFormat},
+};
+
+/// Longest line of an iCalendar file in octets, longer lines are folded
+const ICS_LINE_LENGTH: usize = 75;
+
+/// iCalendar with one event per contest, contests without a start date are skipped
+pub fn contests_to_ics(contests: &[Contest], stamp: DateTime<Utc>) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//c4c//Audit contests//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "X-WR-CALNAME:Audit contests".to_owned(),
+    ];
+
+    for contest in contests {
+        let Some(start_date) = contest.start_date.as_ref() else {
+            continue;
+        };
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!(
+            "UID:{}-{}@c4c",
+            contest.platform.as_str(),
+            escape_text(&contest.id)
+        ));
+        lines.push(format!("DTSTAMP:{}", DateFormat::ICalendar.format(&stamp)));
+        lines.push(format!(
+            "DTSTART:{}",
+            DateFormat::ICalendar.format(start_date)
+        ));
+        if let Some(end_date) = contest.end_date.as_ref() {
+            lines.push(format!("DTEND:{}", DateFormat::ICalendar.format(end_date)));
+        }
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("{} ({})", contest.name, contest.platform.as_str()))
+        ));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(&ContestMessage::from(contest).details().join("\n"))
+        ));
+        lines.push(format!("CATEGORIES:{}", contest.platform.as_str()));
+        lines.push(format!("URL:{}", contest.uri));
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|line| fold_line(line) + "\r\n").collect()
+}
+
+fn escape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            ';' => result.push_str("\\;"),
+            ',' => result.push_str("\\,"),
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Split `line` into lines of at most [`ICS_LINE_LENGTH`] octets, continuation lines start with
+/// a space
+fn fold_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > ICS_LINE_LENGTH {
+            result.push_str("\r\n ");
+            length = 1;
+        }
+        result.push(c);
+        length += c.len_utf8();
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::types::{test_contest, ContestPlatform};
+
+    #[test]
+    fn test_contests_to_ics() {
+        let contest = Contest {
+            name: "Dodo, the aggregator".to_owned(),
+            ..test_contest()
+        };
+        let undated = Contest {
+            platform: ContestPlatform::Code4rena,
+            id: "2023-05-maia".to_owned(),
+            start_date: None,
+            end_date: None,
+            ..contest.clone()
+        };
+        let stamp = Utc.with_ymd_and_hms(2023, 6, 20, 0, 0, 0).unwrap();
+
+        let ics = contests_to_ics(&[contest, undated], stamp);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(lines.contains(&"UID:sherlock-93@c4c"));
+        assert!(lines.contains(&"DTSTAMP:20230620T000000Z"));
+        assert!(lines.contains(&"DTSTART:20230627T150000Z"));
+        assert!(lines.contains(&"DTEND:20230701T150000Z"));
+        assert!(lines.contains(&"SUMMARY:Dodo\\, the aggregator (sherlock)"));
+        assert!(lines.iter().all(|line| line.len() <= ICS_LINE_LENGTH));
+        assert!(ics
+            .replace("\r\n ", "")
+            .contains("\\nPrize: $56\\,000 USDC\\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = "é".repeat(50);
+        let folded = fold_line(&line);
+        assert!(folded
+            .split("\r\n")
+            .all(|line| line.len() <= ICS_LINE_LENGTH));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    notifier::ContestMessage,
+    types::{Contest, DateFormat},
+};
+
+/// Atom feed of `contests`, newest first, with one entry per contest
+pub fn contests_to_atom(contests: &[Contest], updated: DateTime<Utc>) -> String {
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    feed.push_str("  <title>Audit contests</title>\n");
+    feed.push_str("  <id>urn:c4c:contests</id>\n");
+    feed.push_str(&format!(
+        "  <updated>{}</updated>\n",
+        DateFormat::Rfc3339.format(&updated)
+    ));
+    feed.push_str("  <author><name>c4c</name></author>\n");
+
+    for contest in contests.iter().rev() {
+        // platforms without dates keep the generation time
+        let entry_updated = contest.start_date.or(contest.end_date).unwrap_or(updated);
+        feed.push_str("  <entry>\n");
+        feed.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&format!("{} ({})", contest.name, contest.platform.as_str()))
+        ));
+        feed.push_str(&format!("    <id>{}</id>\n", escape_xml(&contest.uri)));
+        feed.push_str(&format!(
+            "    <link rel=\"alternate\" href=\"{}\"/>\n",
+            escape_xml(&contest.uri)
+        ));
+        if let Some(repo_uri) = contest.repo_uri.as_ref() {
+            feed.push_str(&format!(
+                "    <link rel=\"related\" href=\"{}\"/>\n",
+                escape_xml(repo_uri)
+            ));
+        }
+        feed.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            DateFormat::Rfc3339.format(&entry_updated)
+        ));
+        feed.push_str(&format!(
+            "    <category term=\"{}\"/>\n",
+            contest.platform.as_str()
+        ));
+        feed.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            escape_xml(&ContestMessage::from(contest).details().join("\n"))
+        ));
+        feed.push_str("  </entry>\n");
+    }
+
+    feed.push_str("</feed>\n");
+    feed
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::types::test_contest;
+
+    #[test]
+    fn test_contests_to_atom() {
+        let contest = Contest {
+            name: "Dodo & Co".to_owned(),
+            ..test_contest()
+        };
+        let updated = Utc.with_ymd_and_hms(2023, 6, 20, 0, 0, 0).unwrap();
+
+        let feed = contests_to_atom(&[contest], updated);
+        assert!(feed.contains("<updated>2023-06-20T00:00:00Z</updated>"));
+        assert!(feed.contains("<title>Dodo &amp; Co (sherlock)</title>"));
+        assert!(feed.contains("<id>https://app.sherlock.xyz/audits/contests/93</id>"));
+        assert!(feed.contains(
+            "<link rel=\"related\" href=\"https://github.com/sherlock-audit/2023-06-dodo\"/>"
+        ));
+        assert!(feed.contains("<updated>2023-06-27T15:00:00Z</updated>"));
+        assert!(feed.contains("Prize: $56,000 USDC"));
+        assert_eq!(feed.matches("<entry>").count(), 1);
+    }
+}
@@ -0,0 +1,4 @@
+mod import;
+pub use import::*;
+mod render;
+pub use render::*;
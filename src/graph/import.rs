@@ -0,0 +1,270 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use ethers_solc::cache::SolFilesCache;
+use serde::{Deserialize, Serialize};
+
+use super::{render_dot, render_mermaid};
+
+/// Project-wide import graph, keyed by source file relative to the project root
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportGraph {
+    pub project_root: PathBuf,
+    edges: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl ImportGraph {
+    pub fn new<P>(project_root: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            edges: BTreeMap::new(),
+        }
+    }
+
+    /// Build the graph from the imports recorded in the solc cache
+    pub fn from_cache<P>(solc_cache: &SolFilesCache, project_root: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let mut graph = Self::new(project_root);
+        for (file, entry) in solc_cache.files.iter() {
+            let file = graph.relative_path(file);
+            graph.add_file(&file);
+            for import in entry.imports.iter() {
+                let import = graph.relative_path(import);
+                graph.add_edge(&file, &import);
+            }
+        }
+        graph
+    }
+
+    pub fn add_file<P>(&mut self, file: P)
+    where
+        P: AsRef<Path>,
+    {
+        self.edges.entry(file.as_ref().to_path_buf()).or_default();
+    }
+
+    pub fn add_edge<P, Q>(&mut self, from: P, to: Q)
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        self.add_file(&to);
+        self.edges
+            .entry(from.as_ref().to_path_buf())
+            .or_default()
+            .insert(to.as_ref().to_path_buf());
+    }
+
+    pub fn contains<P>(&self, file: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.edges.contains_key(file.as_ref())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.edges.keys()
+    }
+
+    pub fn direct_imports<P>(&self, file: P) -> BTreeSet<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        self.edges.get(file.as_ref()).cloned().unwrap_or_default()
+    }
+
+    /// Every file `file` depends on, directly or not
+    pub fn transitive_imports<P>(&self, file: P) -> BTreeSet<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        let mut result: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut queue: VecDeque<&PathBuf> = VecDeque::new();
+        if let Some(imports) = self.edges.get(file.as_ref()) {
+            queue.extend(imports.iter());
+        }
+
+        while let Some(current) = queue.pop_front() {
+            if !result.insert(current.clone()) {
+                continue;
+            }
+            if let Some(imports) = self.edges.get(current) {
+                queue.extend(imports.iter().filter(|import| !result.contains(*import)));
+            }
+        }
+        result
+    }
+
+    /// Import cycles, as strongly connected components with more than one file or a self import
+    pub fn cycles(&self) -> Vec<Vec<PathBuf>> {
+        strongly_connected_components(&self.edges)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .edges
+                        .get(&component[0])
+                        .is_some_and(|imports| imports.contains(&component[0]))
+            })
+            .collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        render_dot("imports", &self.string_edges())
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        render_mermaid(&self.string_edges())
+    }
+
+    fn string_edges(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.edges
+            .iter()
+            .map(|(file, imports)| {
+                (
+                    file.to_string_lossy().to_string(),
+                    imports
+                        .iter()
+                        .map(|import| import.to_string_lossy().to_string())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn relative_path(&self, file: &Path) -> PathBuf {
+        file.strip_prefix(&self.project_root)
+            .unwrap_or(file)
+            .to_path_buf()
+    }
+}
+
+/// Tarjan's algorithm. Each component is sorted, and components are returned in a
+/// deterministic order
+pub(crate) fn strongly_connected_components<N>(adjacency: &BTreeMap<N, BTreeSet<N>>) -> Vec<Vec<N>>
+where
+    N: Ord + Clone,
+{
+    struct State<N> {
+        index: usize,
+        indices: BTreeMap<N, usize>,
+        low_links: BTreeMap<N, usize>,
+        stack: Vec<N>,
+        on_stack: BTreeSet<N>,
+        components: Vec<Vec<N>>,
+    }
+
+    fn visit<N: Ord + Clone>(node: &N, adjacency: &BTreeMap<N, BTreeSet<N>>, state: &mut State<N>) {
+        state.indices.insert(node.clone(), state.index);
+        state.low_links.insert(node.clone(), state.index);
+        state.index += 1;
+        state.stack.push(node.clone());
+        state.on_stack.insert(node.clone());
+
+        for next in adjacency.get(node).into_iter().flatten() {
+            if !state.indices.contains_key(next) {
+                visit(next, adjacency, state);
+                let low_link = state.low_links[node].min(state.low_links[next]);
+                state.low_links.insert(node.clone(), low_link);
+            } else if state.on_stack.contains(next) {
+                let low_link = state.low_links[node].min(state.indices[next]);
+                state.low_links.insert(node.clone(), low_link);
+            }
+        }
+
+        if state.low_links[node] == state.indices[node] {
+            let mut component: Vec<N> = vec![];
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(&member);
+                let is_root = &member == node;
+                component.push(member);
+                if is_root {
+                    break;
+                }
+            }
+            component.sort();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        index: 0,
+        indices: BTreeMap::new(),
+        low_links: BTreeMap::new(),
+        stack: vec![],
+        on_stack: BTreeSet::new(),
+        components: vec![],
+    };
+    for node in adjacency.keys() {
+        if !state.indices.contains_key(node) {
+            visit(node, adjacency, &mut state);
+        }
+    }
+
+    state.components.sort();
+    state.components
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph() -> ImportGraph {
+        let mut graph = ImportGraph::new("/repo");
+        graph.add_edge("src/Vault.sol", "src/interfaces/IVault.sol");
+        graph.add_edge("src/Vault.sol", "src/libraries/Math.sol");
+        graph.add_edge(
+            "src/libraries/Math.sol",
+            "lib/solmate/src/FixedPointMath.sol",
+        );
+        graph.add_edge("src/A.sol", "src/B.sol");
+        graph.add_edge("src/B.sol", "src/A.sol");
+        graph
+    }
+
+    #[test]
+    fn test_import_graph_transitive_imports() {
+        let imports = graph().transitive_imports("src/Vault.sol");
+
+        assert_eq!(
+            imports,
+            BTreeSet::from([
+                PathBuf::from("lib/solmate/src/FixedPointMath.sol"),
+                PathBuf::from("src/interfaces/IVault.sol"),
+                PathBuf::from("src/libraries/Math.sol"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_import_graph_cycles() {
+        let cycles = graph().cycles();
+
+        assert_eq!(
+            cycles,
+            vec![vec![PathBuf::from("src/A.sol"), PathBuf::from("src/B.sol")]]
+        );
+        assert_eq!(
+            graph().transitive_imports("src/A.sol"),
+            BTreeSet::from([PathBuf::from("src/A.sol"), PathBuf::from("src/B.sol")])
+        );
+    }
+
+    #[test]
+    fn test_import_graph_to_mermaid() {
+        let mut graph = ImportGraph::new("/repo");
+        graph.add_edge("src/Vault.sol", "src/IVault.sol");
+
+        assert_eq!(
+            graph.to_mermaid(),
+            "flowchart LR\n    n0[\"src/IVault.sol\"]\n    n1[\"src/Vault.sol\"]\n    n1 --> n0\n"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Render a directed graph as Graphviz DOT
+pub fn render_dot(name: &str, edges: &BTreeMap<String, BTreeSet<String>>) -> String {
+    let mut result = format!("digraph \"{}\" {{\n", escape(name));
+    result.push_str("    rankdir=LR;\n");
+    for node in nodes(edges) {
+        result.push_str(&format!("    \"{}\";\n", escape(node)));
+    }
+    for (from, targets) in edges.iter() {
+        for to in targets.iter() {
+            result.push_str(&format!(
+                "    \"{}\" -> \"{}\";\n",
+                escape(from),
+                escape(to)
+            ));
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+/// Render a directed graph as a Mermaid flowchart
+pub fn render_mermaid(edges: &BTreeMap<String, BTreeSet<String>>) -> String {
+    let nodes = nodes(edges);
+    let ids: BTreeMap<&str, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (*node, format!("n{}", index)))
+        .collect();
+
+    let mut result = String::from("flowchart LR\n");
+    for node in nodes.iter() {
+        result.push_str(&format!(
+            "    {}[\"{}\"]\n",
+            ids[node],
+            node.replace('"', "#quot;")
+        ));
+    }
+    for (from, targets) in edges.iter() {
+        for to in targets.iter() {
+            result.push_str(&format!(
+                "    {} --> {}\n",
+                ids[from.as_str()],
+                ids[to.as_str()]
+            ));
+        }
+    }
+    result
+}
+
+fn nodes(edges: &BTreeMap<String, BTreeSet<String>>) -> BTreeSet<&str> {
+    edges
+        .iter()
+        .flat_map(|(from, targets)| {
+            std::iter::once(from.as_str()).chain(targets.iter().map(|to| to.as_str()))
+        })
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edges() -> BTreeMap<String, BTreeSet<String>> {
+        let mut edges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        edges
+            .entry("src/A.sol".to_owned())
+            .or_default()
+            .insert("src/B.sol".to_owned());
+        edges.entry("src/B.sol".to_owned()).or_default();
+        edges
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = render_dot("imports", &edges());
+        assert_eq!(
+            dot,
+            "digraph \"imports\" {\n    rankdir=LR;\n    \"src/A.sol\";\n    \"src/B.sol\";\n    \"src/A.sol\" -> \"src/B.sol\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let mermaid = render_mermaid(&edges());
+        assert_eq!(
+            mermaid,
+            "flowchart LR\n    n0[\"src/A.sol\"]\n    n1[\"src/B.sol\"]\n    n0 --> n1\n"
+        );
+    }
+}
@@ -1,6 +1,16 @@
+pub mod analyzer;
 pub mod compiler;
+pub mod config;
 pub mod crawler;
+pub mod daemon;
 pub mod errors;
+pub mod events;
 pub mod export;
+pub mod graph;
+pub mod notifier;
+pub mod pipeline;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod store;
 pub mod types;
 pub mod utils;
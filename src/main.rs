@@ -1,54 +1,405 @@
-use c4_crawler::compiler::{
-    clone_or_pull_repo, find_all_contracts, project_dir_from_uri, ProjectType,
-};
-use c4_crawler::crawler::fetch_all_contests;
-use c4_crawler::types::{Contest, Contract};
-use rr_logging::{error, info, init_tracing};
-use std::collections::HashMap;
+use c4_crawler::analyzer::SimilarityIndex;
+use c4_crawler::compiler::{clone_or_pull_repo, find_all_contracts_with_options};
+use c4_crawler::config::Config;
+use c4_crawler::crawler::{get_crawlers, hide_private_repos};
+use c4_crawler::daemon::{Daemon, DaemonConfig};
+use c4_crawler::export::{export_contest_feeds, export_contracts_to_file};
+use c4_crawler::notifier::get_notifiers;
+use c4_crawler::pipeline::ContestPipeline;
+use c4_crawler::store::ContestStore;
+use c4_crawler::types::{Contest, ContestPlatform, ContestStatus, Contract, DateFormat};
+use clap::{Parser, Subcommand};
+use rr_logging::{error, init_tracing};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+/// Fetch, clone and compile the contests of audit platforms
+#[derive(Debug, Parser)]
+#[command(name = "c4c", version)]
+struct Cli {
+    /// Print JSON instead of text, for scripting
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Config file, defaults to `C4C_CONFIG` or `c4c.toml`
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Fetch the contests of every platform, then clone and build the new or updated ones
+    Crawl,
+    /// List the contests of the platforms
+    List {
+        /// code4rena or sherlock
+        #[arg(long)]
+        platform: Option<ContestPlatform>,
+        /// ongoing, upcoming or ended
+        #[arg(long)]
+        status: Option<ContestStatus>,
+        /// List the contests kept from previous runs instead of fetching the platforms
+        #[arg(long)]
+        stored: bool,
+    },
+    /// Clone a contest repo, or pull it if it's already cloned
+    Fetch {
+        /// e.g. https://github.com/code-423n4/2023-05-maia
+        repo: String,
+    },
+    /// Compile every project of a cloned repo and report the failures
+    Build { dir: PathBuf },
+    /// List the contracts of a cloned repo
+    Contracts {
+        dir: PathBuf,
+        /// Only the contracts declared in the contest scope
+        #[arg(long)]
+        in_scope: bool,
+        /// Also the tests, scripts, mocks and libraries, not only the project's own sources
+        #[arg(long)]
+        all_origins: bool,
+    },
+    /// Write the contracts of a cloned repo to a JSON file
+    Export {
+        dir: PathBuf,
+        #[arg(short, long, default_value = "contracts.json")]
+        output: PathBuf,
+    },
+    /// Write the Atom feed and the iCalendar of the stored contests
+    Feeds {
+        /// Defaults to the export directory of the config
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Keep polling the platforms until SIGTERM
+    Watch,
+    /// Remove a cloned repo, or every cloned repo of the workspace
+    Clean { dir: Option<PathBuf> },
+    /// Serve the REST API over the stored contests
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ContestOutput<'a> {
+    platform: &'static str,
+    id: &'a str,
+    name: &'a str,
+    status: &'static str,
+    uri: &'a str,
+    repo_uri: Option<&'a str>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    reward: Option<&'a str>,
+}
+
+impl<'a> From<&'a Contest> for ContestOutput<'a> {
+    fn from(contest: &'a Contest) -> Self {
+        Self {
+            platform: contest.platform.as_str(),
+            id: &contest.id,
+            name: &contest.name,
+            status: contest.status.as_str(),
+            uri: &contest.uri,
+            repo_uri: contest.repo_uri.as_deref(),
+            start_date: contest
+                .start_date
+                .map(|date| DateFormat::Rfc3339.format(&date)),
+            end_date: contest
+                .end_date
+                .map(|date| DateFormat::Rfc3339.format(&date)),
+            reward: contest.reward.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ContractOutput<'a> {
+    name: &'a str,
+    contract_type: String,
+    origin: String,
+    in_scope: bool,
+    project_root: &'a Path,
+    source_file: &'a Path,
+    nsloc: Option<usize>,
+}
+
+impl<'a> From<&'a Contract> for ContractOutput<'a> {
+    fn from(contract: &'a Contract) -> Self {
+        Self {
+            name: &contract.name,
+            contract_type: format!("{:?}", contract.contract_type),
+            origin: format!("{:?}", contract.origin),
+            in_scope: contract.in_scope,
+            project_root: &contract.project_root,
+            source_file: &contract.source_file,
+            nsloc: contract.metrics.as_ref().map(|metrics| metrics.nsloc),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BuildOutput {
+    contracts: usize,
+    diagnostics: Vec<DiagnosticOutput>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiagnosticOutput {
+    project_root: PathBuf,
+    stage: String,
+    reason: String,
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?.init();
+
     // init logging
-    std::env::set_var("RUST_LOG", "info,headless_chrome=error");
+    if std::env::var_os("RUST_LOG").is_none() {
+        std::env::set_var("RUST_LOG", &config.log);
+    }
     init_tracing(None);
 
-    info!("Starting...");
-
-    let all_contests = fetch_all_contests().await?;
-
-    for mut contest in all_contests {
-        info!("Contest {:#?}", contest);
-        let repo_uri = contest.repo_uri.unwrap();
-        let repo_dir = project_dir_from_uri(&repo_uri);
-        info!("Repo directory {:#?}", repo_dir);
-
-        if let Err(e) = clone_or_pull_repo(&repo_uri) {
-            error!("Clone repo error: {e:?}");
-            continue;
-        };
+    match cli.command {
+        Command::Crawl => {
+            let mut pipeline = pipeline(config)?;
+            for crawler in get_crawlers(config) {
+                if let Err(e) = pipeline.crawl(crawler.as_ref()).await {
+                    error!("Error crawling {:?} contests {e:?}", crawler.platform());
+                }
+            }
+        }
+        Command::List {
+            platform,
+            status,
+            stored,
+        } => {
+            let mut contests: Vec<Contest> = vec![];
+            if stored {
+                let store =
+                    ContestStore::open(ContestStore::default_path(&config.workspace_dir()))?;
+                contests.extend(store.contests()?.into_iter().map(|stored| stored.contest));
+            } else {
+                for crawler in get_crawlers(config)
+                    .into_iter()
+                    .filter(|crawler| platform.is_none_or(|p| p == crawler.platform()))
+                {
+                    contests.extend(hide_private_repos(crawler.fetch_all_contests().await?).await);
+                }
+            }
+            contests.retain(|contest| {
+                platform.is_none_or(|platform| contest.platform == platform)
+                    && status.is_none_or(|status| contest.status == status)
+            });
 
-        let all_contracts = match find_all_contracts(repo_dir) {
-            Ok(result) => result,
-            Err(e) => {
-                error!("Find all contracts error {e:?}");
-                continue;
+            let output: Vec<ContestOutput> = contests.iter().map(ContestOutput::from).collect();
+            print(cli.json, &output, || {
+                output
+                    .iter()
+                    .map(|contest| {
+                        format!(
+                            "{:<10} {:<9} {} {} {}",
+                            contest.platform,
+                            contest.status,
+                            contest.name,
+                            contest.uri,
+                            contest.repo_uri.unwrap_or("(private repo)")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+        Command::Fetch { repo } => {
+            let repo_dir = clone_or_pull_repo(config.workspace_dir(), &repo)?;
+            print(cli.json, &repo_dir, || repo_dir.display().to_string())?;
+        }
+        Command::Build { dir } => {
+            let report =
+                find_all_contracts_with_options(&dir, &config.build.find_contracts_options())?;
+            let output = BuildOutput {
+                contracts: report.contracts.len(),
+                diagnostics: report
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| DiagnosticOutput {
+                        project_root: diagnostic.project_root.clone(),
+                        stage: format!("{:?}", diagnostic.stage),
+                        reason: diagnostic.reason.clone(),
+                    })
+                    .collect(),
+            };
+            print(cli.json, &output, || {
+                let mut lines = vec![format!("Found {} contracts", output.contracts)];
+                lines.extend(output.diagnostics.iter().map(|diagnostic| {
+                    format!(
+                        "Project {} failed at {}: {}",
+                        diagnostic.project_root.display(),
+                        diagnostic.stage,
+                        diagnostic.reason
+                    )
+                }));
+                lines.join("\n")
+            })?;
+        }
+        Command::Contracts {
+            dir,
+            in_scope,
+            all_origins,
+        } => {
+            let mut options = config.build.find_contracts_options();
+            if all_origins {
+                options.origins = vec![];
             }
-        };
+            options.only_in_scope = in_scope;
+            let report = find_all_contracts_with_options(&dir, &options)?;
+
+            let output: Vec<ContractOutput> =
+                report.contracts.iter().map(ContractOutput::from).collect();
+            print(cli.json, &output, || {
+                output
+                    .iter()
+                    .map(|contract| {
+                        format!(
+                            "{:<9} {:<8} {}{} {}",
+                            contract.contract_type,
+                            contract.origin,
+                            contract.name,
+                            if contract.in_scope { " (in scope)" } else { "" },
+                            contract.source_file.display()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+        Command::Export { dir, output } => {
+            let report =
+                find_all_contracts_with_options(&dir, &config.build.find_contracts_options())?;
+            let repo_name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            export_contracts_to_file(&repo_name, &report.contracts, &output)?;
+            print(cli.json, &output, || {
+                format!("Exported contracts to {}", output.display())
+            })?;
+        }
+        Command::Feeds { output_dir } => {
+            let store = ContestStore::open(ContestStore::default_path(&config.workspace_dir()))?;
+            let contests: Vec<Contest> = store
+                .contests()?
+                .into_iter()
+                .map(|stored| stored.contest)
+                .collect();
+            let output_dir = output_dir.unwrap_or_else(|| config.export_dir());
+            let output_files = export_contest_feeds(&contests, &output_dir)?;
+            print(cli.json, &output_files, || {
+                output_files
+                    .iter()
+                    .map(|file| format!("Wrote {}", file.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+        Command::Watch => {
+            Daemon::new(
+                pipeline(config)?,
+                get_crawlers(config),
+                DaemonConfig::default(),
+            )
+            .run()
+            .await?;
+            // don't let the runtime wait for a build abandoned on shutdown
+            std::process::exit(0);
+        }
+        #[cfg(feature = "server")]
+        Command::Serve { addr } => {
+            let store = ContestStore::open(ContestStore::default_path(&config.workspace_dir()))?;
+            let state = c4_crawler::server::ServerState::new(store, pipeline(config)?);
+            c4_crawler::server::serve(addr, state).await?;
+        }
+        Command::Clean { dir } => {
+            let removed = match dir {
+                Some(dir) => vec![clean_repo(config, &dir)?],
+                None => clean_workspace(config)?,
+            };
+            print(cli.json, &removed, || {
+                removed
+                    .iter()
+                    .map(|dir| format!("Removed {}", dir.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        }
+    }
 
-        info!("Found {:#?} contracts", all_contracts.len());
+    Ok(())
+}
 
-        // info!("Repo name {:?}", repo_uri);
-        // for contract in all_contracts.iter() {
-        //     info!("Contract {:#?}", contract);
-        // }
+fn pipeline(config: &Config) -> anyhow::Result<ContestPipeline> {
+    let store = ContestStore::open(ContestStore::default_path(&config.workspace_dir()))?;
+    Ok(ContestPipeline::new(
+        store,
+        SimilarityIndex::default_path(&config.workspace_dir()),
+        get_notifiers(),
+        config,
+    ))
+}
 
-        contest.contracts = all_contracts;
+/// Remove a cloned repo, refusing paths outside of the workspace
+fn clean_repo(config: &Config, dir: &Path) -> anyhow::Result<PathBuf> {
+    let workspace = fs::canonicalize(config.workspace_dir())?;
+    let dir = fs::canonicalize(dir)?;
+    if !dir.starts_with(&workspace) || dir == workspace {
+        anyhow::bail!(
+            "{} is not a repo of the workspace {}",
+            dir.display(),
+            workspace.display()
+        );
     }
+    fs::remove_dir_all(&dir)?;
+    Ok(dir)
+}
 
-    // let repo_uri = "https://github.com/code-423n4/2023-05-maia";
+/// Remove the cloned repos, keeping the store, the similarity index and the exports. Only git
+/// checkouts are removed, so a workspace pointing at another directory is left untouched.
+fn clean_workspace(config: &Config) -> anyhow::Result<Vec<PathBuf>> {
+    let mut removed: Vec<PathBuf> = vec![];
+    let workspace = config.workspace_dir();
+    if !workspace.exists() {
+        return Ok(removed);
+    }
+    let export_dir = config.export_dir();
+    for entry in fs::read_dir(&workspace)? {
+        let path = entry?.path();
+        if path.is_dir() && path != export_dir && path.join(".git").exists() {
+            fs::remove_dir_all(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
 
+/// Print `value` as JSON, or the `text` rendering of it
+fn print<T, F>(json: bool, value: &T, text: F) -> anyhow::Result<()>
+where
+    T: Serialize,
+    F: FnOnce() -> String,
+{
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", text());
+    }
     Ok(())
 }
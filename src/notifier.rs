@@ -0,0 +1,257 @@
+use std::{env, sync::Arc};
+
+use rr_logging::{error, warn};
+use serde::Serialize;
+
+use crate::{
+    errors::AppError,
+    events::ContestEvent,
+    types::{Contest, DateFormat},
+};
+
+use self::email::{EmailNotifier, SmtpConfig};
+use self::webhook::WebhookNotifier;
+
+pub mod email;
+pub mod webhook;
+
+/// A sink for contest events
+#[async_trait::async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, message: &ContestMessage) -> Result<(), AppError>;
+}
+
+/// Notification of a contest event, rendered by each sink
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContestMessage {
+    /// See [`ContestEvent::kind`]
+    pub event: &'static str,
+    pub title: String,
+    pub name: String,
+    pub platform: &'static str,
+    pub uri: String,
+    pub repo_uri: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub reward: Option<String>,
+    /// nSLOC in scope, or of every contract if the scope is unknown
+    pub nsloc: Option<usize>,
+}
+
+impl ContestMessage {
+    /// Message of an event, using `contest` for the latest data such as its built contracts
+    pub fn new(event: &ContestEvent, contest: &Contest) -> Self {
+        Self {
+            event: event.kind(),
+            title: event.to_string(),
+            ..Self::from(contest)
+        }
+    }
+
+    /// Plain text body, one field per line
+    pub fn text(&self) -> String {
+        let mut lines = vec![self.title.clone()];
+        lines.extend(self.details());
+        lines.join("\n")
+    }
+
+    /// Platform, dates, prize, nSLOC and repo, one `Field: value` line each
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = vec![format!("Platform: {}", self.platform)];
+        if let Some(start_date) = &self.start_date {
+            lines.push(format!("Start: {start_date}"));
+        }
+        if let Some(end_date) = &self.end_date {
+            lines.push(format!("End: {end_date}"));
+        }
+        if let Some(reward) = &self.reward {
+            lines.push(format!("Prize: {reward}"));
+        }
+        if let Some(nsloc) = self.nsloc {
+            lines.push(format!("nSLOC: {nsloc}"));
+        }
+        if let Some(repo_uri) = &self.repo_uri {
+            lines.push(format!("Repo: {repo_uri}"));
+        }
+        lines
+    }
+}
+
+/// Message describing a contest without an event, as in the feeds
+impl From<&Contest> for ContestMessage {
+    fn from(contest: &Contest) -> Self {
+        let metrics = contest.metrics();
+        let nsloc = if metrics.in_scope_contracts > 0 {
+            Some(metrics.in_scope.nsloc)
+        } else if metrics.contracts > 0 {
+            Some(metrics.total.nsloc)
+        } else {
+            None
+        };
+
+        Self {
+            event: "contest",
+            title: contest.name.clone(),
+            name: contest.name.clone(),
+            platform: contest.platform.as_str(),
+            uri: contest.uri.clone(),
+            repo_uri: contest.repo_uri.clone(),
+            start_date: contest
+                .start_date
+                .as_ref()
+                .map(|date| DateFormat::Text.format(date)),
+            end_date: contest
+                .end_date
+                .as_ref()
+                .map(|date| DateFormat::Text.format(date)),
+            reward: contest.reward.clone(),
+            nsloc,
+        }
+    }
+}
+
+/// Notifiers configured by environment variables:
+/// - `C4C_WEBHOOK_URL`, `C4C_SLACK_WEBHOOK_URL`, `C4C_DISCORD_WEBHOOK_URL`
+/// - `C4C_SMTP_HOST`, `C4C_SMTP_PORT`, `C4C_SMTP_USERNAME`, `C4C_SMTP_PASSWORD`,
+///   `C4C_SMTP_FROM` and the comma separated `C4C_SMTP_TO`
+pub fn get_notifiers() -> Vec<Arc<dyn Notifier>> {
+    let mut notifiers: Vec<Arc<dyn Notifier>> = vec![];
+    if let Ok(url) = env::var("C4C_WEBHOOK_URL") {
+        notifiers.push(Arc::new(WebhookNotifier::json(url)));
+    }
+    if let Ok(url) = env::var("C4C_SLACK_WEBHOOK_URL") {
+        notifiers.push(Arc::new(WebhookNotifier::slack(url)));
+    }
+    if let Ok(url) = env::var("C4C_DISCORD_WEBHOOK_URL") {
+        notifiers.push(Arc::new(WebhookNotifier::discord(url)));
+    }
+    if let Ok(host) = env::var("C4C_SMTP_HOST") {
+        let config = SmtpConfig {
+            host,
+            port: env::var("C4C_SMTP_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok()),
+            username: env::var("C4C_SMTP_USERNAME").ok(),
+            password: env::var("C4C_SMTP_PASSWORD").ok(),
+            plaintext: false,
+            from: env::var("C4C_SMTP_FROM").unwrap_or_default(),
+            to: env::var("C4C_SMTP_TO")
+                .unwrap_or_default()
+                .split(',')
+                .map(|address| address.trim().to_owned())
+                .filter(|address| !address.is_empty())
+                .collect(),
+        };
+        match EmailNotifier::new(&config) {
+            Ok(notifier) => notifiers.push(Arc::new(notifier)),
+            Err(e) => warn!("Invalid SMTP configuration {e:?}"),
+        }
+    }
+    notifiers
+}
+
+/// Send a message to every notifier, logging the failures
+pub async fn notify_all(notifiers: &[Arc<dyn Notifier>], message: &ContestMessage) {
+    for notifier in notifiers.iter() {
+        if let Err(e) = notifier.notify(message).await {
+            error!("Notify error {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::test_contest;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    pub(crate) fn test_message() -> ContestMessage {
+        let contest = test_contest();
+        ContestMessage::new(&ContestEvent::ContestStarted(contest.clone()), &contest)
+    }
+
+    /// Accept one HTTP request and return its body
+    pub(crate) async fn serve_http_once(listener: TcpListener) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request: Vec<u8> = vec![];
+        let mut buffer = [0u8; 1024];
+        loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length || read == 0 {
+                    stream
+                        .write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n")
+                        .await
+                        .unwrap();
+                    return body.to_owned();
+                }
+            }
+        }
+    }
+
+    /// Accept one SMTP session and return the DATA it received
+    pub(crate) async fn serve_smtp_once(listener: TcpListener) -> String {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+        let mut data: Vec<String> = vec![];
+        let mut in_data = false;
+        while let Some(line) = lines.next_line().await.unwrap() {
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    writer.write_all(b"250 OK\r\n").await.unwrap();
+                } else {
+                    data.push(line);
+                }
+                continue;
+            }
+            let command = line.to_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                b"250 localhost\r\n"
+            } else if command.starts_with("DATA") {
+                in_data = true;
+                b"354 End data with <CR><LF>.<CR><LF>\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+        data.join("\n")
+    }
+
+    #[test]
+    fn test_contest_message() {
+        let message = test_message();
+
+        assert_eq!(message.event, "contest_started");
+        assert_eq!(message.nsloc, None);
+        assert_eq!(
+            message.text(),
+            "Contest Dodo started (https://app.sherlock.xyz/audits/contests/93)\n\
+            Platform: sherlock\n\
+            Start: 2023-06-27 15:00 UTC\n\
+            End: 2023-07-01 15:00 UTC\n\
+            Prize: $56,000 USDC\n\
+            Repo: https://github.com/sherlock-audit/2023-06-dodo"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Deserialize;
+
+use crate::errors::AppError;
+
+use super::{ContestMessage, Notifier};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to 587 with STARTTLS, or 25 in plaintext
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Connect without TLS, only for a local relay
+    #[serde(default)]
+    pub plaintext: bool,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Send contest messages by email
+#[derive(Clone)]
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailNotifier {
+    pub fn new(config: &SmtpConfig) -> Result<Self, AppError> {
+        let mut builder = if config.plaintext {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse()?,
+            to: config
+                .to
+                .iter()
+                .map(|address| address.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn email(&self, message: &ContestMessage) -> Result<Message, AppError> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(&message.title)
+            .header(ContentType::TEXT_PLAIN);
+        for to in self.to.iter() {
+            builder = builder.to(to.clone());
+        }
+        Ok(builder.body(message.text())?)
+    }
+}
+
+#[async_trait::async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, message: &ContestMessage) -> Result<(), AppError> {
+        self.transport.send(self.email(message)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::notifier::test::{serve_smtp_once, test_message};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_email_notifier() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(serve_smtp_once(listener));
+
+        let notifier = EmailNotifier::new(&SmtpConfig {
+            host: "127.0.0.1".to_owned(),
+            port: Some(port),
+            plaintext: true,
+            from: "c4c <c4c@localhost>".to_owned(),
+            to: vec!["team@localhost".to_owned()],
+            ..Default::default()
+        })
+        .unwrap();
+        notifier.notify(&test_message()).await.unwrap();
+        drop(notifier);
+
+        let data = server.await.unwrap();
+        assert!(data.contains("To: team@localhost"));
+        assert!(data.contains("Subject: Contest Dodo started"));
+        assert!(data.contains("Prize: $56,000 USDC"));
+    }
+}
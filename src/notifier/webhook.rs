@@ -0,0 +1,110 @@
+use serde_json::{json, Value};
+
+use crate::errors::AppError;
+
+use super::{ContestMessage, Notifier};
+
+/// Discord rejects messages longer than this
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The [`ContestMessage`] as JSON
+    Json,
+    /// Slack incoming webhook
+    Slack,
+    /// Discord webhook
+    Discord,
+}
+
+/// Post contest messages to a webhook
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    format: WebhookFormat,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, format: WebhookFormat) -> Self {
+        Self {
+            url,
+            format,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn json(url: String) -> Self {
+        Self::new(url, WebhookFormat::Json)
+    }
+
+    pub fn slack(url: String) -> Self {
+        Self::new(url, WebhookFormat::Slack)
+    }
+
+    pub fn discord(url: String) -> Self {
+        Self::new(url, WebhookFormat::Discord)
+    }
+
+    pub fn payload(&self, message: &ContestMessage) -> Result<Value, AppError> {
+        Ok(match self.format {
+            WebhookFormat::Json => serde_json::to_value(message)?,
+            WebhookFormat::Slack => json!({ "text": message.text() }),
+            WebhookFormat::Discord => json!({
+                "content": message
+                    .text()
+                    .chars()
+                    .take(DISCORD_MAX_CONTENT)
+                    .collect::<String>()
+            }),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, message: &ContestMessage) -> Result<(), AppError> {
+        self.client
+            .post(&self.url)
+            .json(&self.payload(message)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::notifier::test::{serve_http_once, test_message};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_webhook_notifier() {
+        let message = test_message();
+        for format in [
+            WebhookFormat::Json,
+            WebhookFormat::Slack,
+            WebhookFormat::Discord,
+        ] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            let server = tokio::spawn(serve_http_once(listener));
+
+            let notifier = WebhookNotifier::new(url, format);
+            notifier.notify(&message).await.unwrap();
+
+            let body: Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+            assert_eq!(body, notifier.payload(&message).unwrap());
+            match format {
+                WebhookFormat::Json => {
+                    assert_eq!(body["event"], "contest_started");
+                    assert_eq!(body["platform"], "sherlock");
+                }
+                WebhookFormat::Slack => assert_eq!(body["text"], message.text()),
+                WebhookFormat::Discord => assert_eq!(body["content"], message.text()),
+            }
+        }
+    }
+}
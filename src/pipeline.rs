@@ -0,0 +1,451 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use rr_logging::{error, info, instrument, tracing, warn};
+
+use crate::{
+    analyzer::SimilarityIndex,
+    compiler::{
+        clone_or_pull_repo, find_all_contracts_with_options, remote_commit, repo_commit,
+        FindContractsOptions,
+    },
+    config::{Config, ExportConfig},
+    crawler::{hide_private_repos, ContestCrawler},
+    errors::AppError,
+    events::{ContestEvent, ContestEventResolver, ContestSnapshot},
+    export::export_contest_feeds,
+    notifier::{notify_all, ContestMessage, Notifier},
+    store::{BuildStatus, ContestStore, StoredContest},
+    types::{Contest, ContestPlatform, ContestStatus, Contract},
+};
+
+/// Turn fetched contests into events, builds and notifications, keeping the state in a
+/// [`ContestStore`]
+pub struct ContestPipeline {
+    store: ContestStore,
+    similarity_index: SimilarityIndex,
+    similarity_index_path: PathBuf,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    find_options: FindContractsOptions,
+    export: ExportConfig,
+    export_dir: PathBuf,
+    workspace_dir: PathBuf,
+}
+
+impl ContestPipeline {
+    pub fn new(
+        store: ContestStore,
+        similarity_index_path: PathBuf,
+        notifiers: Vec<Arc<dyn Notifier>>,
+        config: &Config,
+    ) -> Self {
+        let similarity_index = SimilarityIndex::load(&similarity_index_path).unwrap_or_else(|e| {
+            warn!("Load similarity index error {e:?}");
+            SimilarityIndex::default()
+        });
+        Self {
+            store,
+            similarity_index,
+            similarity_index_path,
+            notifiers,
+            find_options: config.build.find_contracts_options(),
+            export: config.export.clone(),
+            export_dir: config.export_dir(),
+            workspace_dir: config.workspace_dir(),
+        }
+    }
+
+    pub fn store(&self) -> &ContestStore {
+        &self.store
+    }
+
+    /// Fetch the contests of a crawler and process them
+    #[instrument(skip_all)]
+    pub async fn crawl(
+        &mut self,
+        crawler: &dyn ContestCrawler,
+    ) -> Result<Vec<ContestEvent>, AppError> {
+        let contests = hide_private_repos(crawler.fetch_all_contests().await?).await;
+        self.process(crawler.platform(), contests).await
+    }
+
+    /// Store the contests fetched from a platform, build the repos whose commit changed and
+    /// notify the events
+    pub async fn process(
+        &mut self,
+        platform: ContestPlatform,
+        contests: Vec<Contest>,
+    ) -> Result<Vec<ContestEvent>, AppError> {
+        let previous: Vec<StoredContest> = self
+            .store
+            .contests()?
+            .into_iter()
+            .filter(|stored| stored.contest.platform == platform)
+            .collect();
+        let current: Vec<ContestSnapshot> = contests
+            .into_iter()
+            .map(|contest| ContestSnapshot {
+                repo_commit: contest.repo_uri.as_deref().and_then(remote_commit),
+                contest,
+            })
+            .collect();
+
+        let events = ContestEventResolver::diff(
+            &previous
+                .iter()
+                .cloned()
+                .map(ContestSnapshot::from)
+                .collect::<Vec<_>>(),
+            &current,
+            &[platform],
+        );
+        for event in events.iter() {
+            info!("{event}");
+            if let ContestEvent::ContestEnded(contest) = event {
+                self.store
+                    .set_status(contest.platform, &contest.id, ContestStatus::Ended)?;
+            }
+        }
+
+        let mut built_contests: Vec<Contest> = vec![];
+        for ContestSnapshot {
+            mut contest,
+            repo_commit,
+        } in current
+        {
+            self.store.upsert_contest(&contest)?;
+            let stored = previous.iter().find(|stored| {
+                stored.contest.platform == contest.platform && stored.contest.id == contest.id
+            });
+            let has_build_event = events.iter().any(|event| {
+                event.requires_build()
+                    && event.contest().platform == contest.platform
+                    && event.contest().id == contest.id
+            });
+            if (has_build_event || stored.is_some_and(Self::needs_retry))
+                && self.build(&mut contest, repo_commit.as_deref())
+            {
+                built_contests.push(contest);
+            }
+        }
+
+        notify(&self.notifiers, &events, &built_contests).await;
+        self.save_similarity_index();
+        self.export_feeds();
+        Ok(events)
+    }
+
+    /// Rebuild the ongoing contests whose repo has a new commit, without fetching the platforms
+    #[instrument(skip_all)]
+    pub async fn refresh_ongoing(&mut self) -> Result<Vec<ContestEvent>, AppError> {
+        let mut events: Vec<ContestEvent> = vec![];
+        let mut built_contests: Vec<Contest> = vec![];
+        for stored in self.store.contests_by_status(ContestStatus::Ongoing)? {
+            let Some(commit) = stored.contest.repo_uri.as_deref().and_then(remote_commit) else {
+                continue;
+            };
+            let event = stored
+                .repo_commit
+                .clone()
+                .filter(|previous_commit| previous_commit != &commit)
+                .map(|previous_commit| ContestEvent::RepoUpdated {
+                    contest: stored.contest.clone(),
+                    previous_commit,
+                    commit: commit.clone(),
+                });
+            if !event.as_ref().is_some_and(ContestEvent::requires_build)
+                && !Self::needs_retry(&stored)
+            {
+                continue;
+            }
+            events.extend(event);
+            let mut contest = stored.contest;
+            if self.build(&mut contest, Some(&commit)) {
+                built_contests.push(contest);
+            }
+        }
+
+        notify(&self.notifiers, &events, &built_contests).await;
+        self.save_similarity_index();
+        Ok(events)
+    }
+
+    /// Pull and rebuild a stored contest, even if its commit didn't change. Returns `None` if
+    /// the contest isn't stored.
+    #[instrument(skip(self))]
+    pub async fn refresh_contest(
+        &mut self,
+        platform: ContestPlatform,
+        id: &str,
+    ) -> Result<Option<Vec<ContestEvent>>, AppError> {
+        let Some(stored) = self.store.contest(platform, id)? else {
+            return Ok(None);
+        };
+        let commit = stored.contest.repo_uri.as_deref().and_then(remote_commit);
+        let mut events: Vec<ContestEvent> = vec![];
+        if let (Some(previous_commit), Some(commit)) = (stored.repo_commit.clone(), commit.clone())
+        {
+            if previous_commit != commit {
+                events.push(ContestEvent::RepoUpdated {
+                    contest: stored.contest.clone(),
+                    previous_commit,
+                    commit,
+                });
+            }
+        }
+
+        let mut contest = stored.contest;
+        let mut built_contests: Vec<Contest> = vec![];
+        if self.build(&mut contest, commit.as_deref()) {
+            built_contests.push(contest);
+        }
+
+        notify(&self.notifiers, &events, &built_contests).await;
+        self.save_similarity_index();
+        Ok(Some(events))
+    }
+
+    /// Whether a contest must be built again without an event requiring it: it was never built,
+    /// or neither the clone nor `ls-remote` worked, so retry until the remote is reachable
+    fn needs_retry(stored: &StoredContest) -> bool {
+        match stored.build_status {
+            BuildStatus::Pending => true,
+            BuildStatus::Failed(_) => stored.repo_commit.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Clone or pull the contest repo and find its contracts, returns whether it succeeded
+    fn build(&mut self, contest: &mut Contest, remote_commit: Option<&str>) -> bool {
+        let Some(repo_uri) = contest.repo_uri.clone() else {
+            return false;
+        };
+        info!("Building contest {:?} from {:?}", contest.name, repo_uri);
+
+        let repo_dir = match clone_or_pull_repo(&self.workspace_dir, &repo_uri) {
+            Ok(repo_dir) => repo_dir,
+            Err(e) => {
+                error!("Clone repo error: {e:?}");
+                // retried once the remote commit changes
+                self.record_build(contest, remote_commit, BuildStatus::Failed(e.to_string()));
+                return false;
+            }
+        };
+        let commit = repo_commit(&repo_dir).or(remote_commit.map(|commit| commit.to_owned()));
+
+        let report = match find_all_contracts_with_options(&repo_dir, &self.find_options) {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Find all contracts error {e:?}");
+                self.record_build(
+                    contest,
+                    commit.as_deref(),
+                    BuildStatus::Failed(e.to_string()),
+                );
+                return false;
+            }
+        };
+        for diagnostic in report.diagnostics.iter() {
+            warn!(
+                "Project {:?} failed at {:?}: {}",
+                diagnostic.project_root, diagnostic.stage, diagnostic.reason
+            );
+        }
+        info!("Found {:#?} contracts", report.contracts.len());
+
+        self.similarity_index
+            .add_contest(&contest.uri, &report.contracts);
+        self.export(&repo_dir, &report.contracts);
+        contest.contracts = report.contracts;
+        self.record_build(contest, commit.as_deref(), BuildStatus::Succeeded);
+        if let Err(e) =
+            self.store
+                .record_import_graphs(contest.platform, &contest.id, &report.import_graphs)
+        {
+            error!("Store import graphs error {e:?}");
+        }
+        true
+    }
+
+    fn export(&self, repo_dir: &Path, contracts: &[Contract]) {
+        let repo_name = repo_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for exporter in self.export.exporters.iter() {
+            match exporter.export(&repo_name, contracts, &self.export_dir) {
+                Ok(output_file) => info!("Exported {exporter:?} to {output_file:?}"),
+                Err(e) => error!("Export {exporter:?} error {e:?}"),
+            }
+        }
+    }
+
+    /// Regenerate the contest feeds from the store, so they include every platform
+    fn export_feeds(&self) {
+        if !self.export.feeds {
+            return;
+        }
+        let contests: Vec<Contest> = match self.store.contests() {
+            Ok(contests) => contests.into_iter().map(|stored| stored.contest).collect(),
+            Err(e) => {
+                error!("Load contests for feeds error {e:?}");
+                return;
+            }
+        };
+        match export_contest_feeds(&contests, &self.export_dir) {
+            Ok(output_files) => info!("Exported contest feeds to {output_files:?}"),
+            Err(e) => error!("Export contest feeds error {e:?}"),
+        }
+    }
+
+    fn record_build(&mut self, contest: &Contest, commit: Option<&str>, status: BuildStatus) {
+        if let Err(e) = self.store.record_build(contest, commit, &status) {
+            error!("Store build error {e:?}");
+        }
+    }
+
+    fn save_similarity_index(&self) {
+        if let Err(e) = self.similarity_index.save(&self.similarity_index_path) {
+            error!("Save similarity index error {e:?}");
+        }
+    }
+}
+
+/// Notify the events, with the built contracts of their contest. Takes the notifiers rather than
+/// the pipeline so that its futures stay `Send`, the store connection isn't `Sync`.
+async fn notify(
+    notifiers: &[Arc<dyn Notifier>],
+    events: &[ContestEvent],
+    built_contests: &[Contest],
+) {
+    for event in events.iter() {
+        let contest = built_contests
+            .iter()
+            .find(|built| {
+                built.platform == event.contest().platform && built.id == event.contest().id
+            })
+            .unwrap_or(event.contest());
+        notify_all(notifiers, &ContestMessage::new(event, contest)).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::types::test_contest;
+
+    struct StubCrawler {
+        contests: Vec<Contest>,
+    }
+
+    #[async_trait::async_trait]
+    impl ContestCrawler for StubCrawler {
+        fn platform(&self) -> ContestPlatform {
+            ContestPlatform::Sherlock
+        }
+
+        async fn fetch_all_contests(&self) -> Result<Vec<Contest>, AppError> {
+            Ok(self.contests.clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        events: Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, message: &ContestMessage) -> Result<(), AppError> {
+            self.events.lock().unwrap().push(message.event);
+            Ok(())
+        }
+    }
+
+    fn test_pipeline(dir: &Path, notifiers: Vec<Arc<dyn Notifier>>) -> ContestPipeline {
+        let config = Config {
+            workspace_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        ContestPipeline::new(
+            ContestStore::open_in_memory().unwrap(),
+            dir.join("similarity_index.json"),
+            notifiers,
+            &config,
+        )
+    }
+
+    fn stored(repo_commit: Option<&str>, build_status: BuildStatus) -> StoredContest {
+        StoredContest {
+            contest: test_contest(),
+            repo_commit: repo_commit.map(str::to_owned),
+            build_status,
+            first_seen_at: Utc::now(),
+            last_seen_at: Utc::now(),
+            built_at: None,
+        }
+    }
+
+    #[test]
+    fn test_needs_retry() {
+        let needs_retry = |commit, status| ContestPipeline::needs_retry(&stored(commit, status));
+        let failed = BuildStatus::Failed("unreachable".to_owned());
+
+        // rebuilt by a `RepoUpdated` event when the remote commit changes
+        assert!(!needs_retry(Some("abc"), BuildStatus::Succeeded));
+        assert!(!needs_retry(Some("abc"), failed.clone()));
+        // never built, or failed before a commit was known
+        assert!(needs_retry(None, BuildStatus::Pending));
+        assert!(needs_retry(None, failed));
+    }
+
+    #[tokio::test]
+    async fn test_crawl() {
+        let dir = tempfile::tempdir().unwrap();
+        let notifier = Arc::new(RecordingNotifier::default());
+        let mut pipeline = test_pipeline(dir.path(), vec![notifier.clone()]);
+        // private repos aren't built nor checked remotely
+        let contest = Contest {
+            repo_uri: None,
+            ..test_contest()
+        };
+        let crawler = StubCrawler {
+            contests: vec![contest.clone()],
+        };
+
+        let events = pipeline.crawl(&crawler).await.unwrap();
+        assert!(matches!(events[..], [ContestEvent::ContestAdded(_)]));
+        let stored = pipeline
+            .store()
+            .contest(contest.platform, &contest.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.build_status, BuildStatus::Pending);
+        assert!(dir.path().join("exports/contests.atom").exists());
+
+        // unchanged contests raise no events
+        assert!(pipeline.crawl(&crawler).await.unwrap().is_empty());
+
+        let events = pipeline
+            .crawl(&StubCrawler { contests: vec![] })
+            .await
+            .unwrap();
+        assert!(matches!(events[..], [ContestEvent::ContestEnded(_)]));
+        let stored = pipeline
+            .store()
+            .contest(contest.platform, &contest.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.contest.status, ContestStatus::Ended);
+        assert_eq!(
+            *notifier.events.lock().unwrap(),
+            vec!["contest_added", "contest_ended"]
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use rr_logging::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::{runtime::Handle, task};
+
+use crate::{
+    errors::AppError,
+    pipeline::ContestPipeline,
+    store::{BuildStatus, ContestStore, StoredContest, StoredContract},
+    types::{ContestPlatform, ContestStatus, DateFormat},
+};
+
+/// Data of the API: the store the pipeline writes to, read through its own connection so that
+/// reads aren't blocked by a running refresh
+#[derive(Clone)]
+pub struct ServerState {
+    store: Arc<Mutex<ContestStore>>,
+    pipeline: Arc<tokio::sync::Mutex<ContestPipeline>>,
+}
+
+impl ServerState {
+    pub fn new(store: ContestStore, pipeline: ContestPipeline) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            pipeline: Arc::new(tokio::sync::Mutex::new(pipeline)),
+        }
+    }
+}
+
+/// Routes of the API:
+/// - `GET /contests?platform=&status=`
+/// - `GET /contests/:platform/:id`, with the contracts and the build status
+/// - `GET /contests/:platform/:id/contracts/:name/{abi,imports,metrics}?source_file=`
+/// - `POST /contests/:platform/:id/refresh`, pulls and rebuilds the repo in the background
+pub fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/contests", get(list_contests))
+        .route("/contests/:platform/:id", get(contest_details))
+        .route("/contests/:platform/:id/refresh", post(refresh_contest))
+        .route(
+            "/contests/:platform/:id/contracts/:name/abi",
+            get(contract_abi),
+        )
+        .route(
+            "/contests/:platform/:id/contracts/:name/imports",
+            get(contract_imports),
+        )
+        .route(
+            "/contests/:platform/:id/contracts/:name/metrics",
+            get(contract_metrics),
+        )
+        .with_state(state)
+}
+
+pub async fn serve(addr: SocketAddr, state: ServerState) -> Result<(), AppError> {
+    info!("Serving the API on {addr}");
+    axum::Server::bind(&addr)
+        .serve(router(state).into_make_service())
+        .await
+        .map_err(|e| AppError::UnknownError(anyhow::anyhow!(e)))
+}
+
+#[derive(Debug)]
+enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(AppError),
+}
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        Self::Internal(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            Self::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            Self::Internal(e) => {
+                error!("API error {e:?}");
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Serialize)]
+struct ContestSummary {
+    platform: &'static str,
+    id: String,
+    name: String,
+    status: &'static str,
+    uri: String,
+    repo_uri: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    reward: Option<String>,
+    repo_commit: Option<String>,
+    build_status: &'static str,
+    build_error: Option<String>,
+    built_at: Option<String>,
+    first_seen_at: String,
+    last_seen_at: String,
+}
+
+impl From<StoredContest> for ContestSummary {
+    fn from(stored: StoredContest) -> Self {
+        let contest = stored.contest;
+        Self {
+            platform: contest.platform.as_str(),
+            id: contest.id,
+            name: contest.name,
+            status: contest.status.as_str(),
+            uri: contest.uri,
+            repo_uri: contest.repo_uri,
+            start_date: contest
+                .start_date
+                .as_ref()
+                .map(|date| DateFormat::Rfc3339.format(date)),
+            end_date: contest
+                .end_date
+                .as_ref()
+                .map(|date| DateFormat::Rfc3339.format(date)),
+            reward: contest.reward,
+            repo_commit: stored.repo_commit,
+            build_status: stored.build_status.as_str(),
+            build_error: match stored.build_status {
+                BuildStatus::Failed(error) => Some(error),
+                _ => None,
+            },
+            built_at: stored
+                .built_at
+                .as_ref()
+                .map(|date| DateFormat::Rfc3339.format(date)),
+            first_seen_at: DateFormat::Rfc3339.format(&stored.first_seen_at),
+            last_seen_at: DateFormat::Rfc3339.format(&stored.last_seen_at),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ContestDetails {
+    #[serde(flatten)]
+    summary: ContestSummary,
+    description: String,
+    contracts: Vec<ContractSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct ContractSummary {
+    name: String,
+    project_root: PathBuf,
+    source_file: PathBuf,
+    contract_type: String,
+    version: String,
+    in_scope: bool,
+    origin: String,
+    nsloc: Option<usize>,
+}
+
+impl From<StoredContract> for ContractSummary {
+    fn from(contract: StoredContract) -> Self {
+        Self {
+            nsloc: contract.metrics.map(|metrics| metrics.nsloc),
+            name: contract.name,
+            project_root: contract.project_root,
+            source_file: contract.source_file,
+            contract_type: contract.contract_type,
+            version: contract.version.to_string(),
+            in_scope: contract.in_scope,
+            origin: contract.origin,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ContractImports {
+    project_root: PathBuf,
+    source_file: PathBuf,
+    imports: Vec<PathBuf>,
+    transitive_imports: Vec<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContestsQuery {
+    platform: Option<String>,
+    status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractQuery {
+    /// Picks among contracts of the same name, relative to their project root
+    source_file: Option<PathBuf>,
+}
+
+fn parse_platform(platform: &str) -> Result<ContestPlatform, ApiError> {
+    platform.parse().map_err(ApiError::NotFound)
+}
+
+fn stored_contest(
+    state: &ServerState,
+    platform: &str,
+    id: &str,
+) -> Result<StoredContest, ApiError> {
+    let platform = parse_platform(platform)?;
+    let store = state.store.lock().expect("store lock poisoned");
+    store
+        .contest(platform, id)?
+        .ok_or_else(|| ApiError::NotFound(format!("Contest {} {id} not found", platform.as_str())))
+}
+
+fn stored_contract(
+    state: &ServerState,
+    platform: &str,
+    id: &str,
+    name: &str,
+    query: &ContractQuery,
+) -> Result<StoredContract, ApiError> {
+    let platform = parse_platform(platform)?;
+    let store = state.store.lock().expect("store lock poisoned");
+    store
+        .contracts_for_contest(platform, id)?
+        .into_iter()
+        .find(|contract| {
+            contract.name == name
+                && query
+                    .source_file
+                    .as_ref()
+                    .is_none_or(|source_file| &contract.source_file == source_file)
+        })
+        .ok_or_else(|| ApiError::NotFound(format!("Contract {name} not found")))
+}
+
+async fn list_contests(
+    State(state): State<ServerState>,
+    Query(query): Query<ContestsQuery>,
+) -> ApiResult<Vec<ContestSummary>> {
+    let platform: Option<ContestPlatform> = query
+        .platform
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let status: Option<ContestStatus> = query
+        .status
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+
+    let store = state.store.lock().expect("store lock poisoned");
+    let contests = match status {
+        Some(status) => store.contests_by_status(status)?,
+        None => store.contests()?,
+    };
+    Ok(Json(
+        contests
+            .into_iter()
+            .filter(|stored| platform.is_none_or(|platform| stored.contest.platform == platform))
+            .map(ContestSummary::from)
+            .collect(),
+    ))
+}
+
+async fn contest_details(
+    State(state): State<ServerState>,
+    Path((platform, id)): Path<(String, String)>,
+) -> ApiResult<ContestDetails> {
+    let stored = stored_contest(&state, &platform, &id)?;
+    let contracts = state
+        .store
+        .lock()
+        .expect("store lock poisoned")
+        .contracts_for_contest(stored.contest.platform, &id)?;
+    Ok(Json(ContestDetails {
+        description: stored.contest.description.clone(),
+        summary: ContestSummary::from(stored),
+        contracts: contracts.into_iter().map(ContractSummary::from).collect(),
+    }))
+}
+
+async fn contract_abi(
+    State(state): State<ServerState>,
+    Path((platform, id, name)): Path<(String, String, String)>,
+    Query(query): Query<ContractQuery>,
+) -> Result<Response, ApiError> {
+    let contract = stored_contract(&state, &platform, &id, &name, &query)?;
+    let abi = contract
+        .abi
+        .ok_or_else(|| ApiError::NotFound(format!("ABI of {name} not found")))?;
+    Ok(Json(abi).into_response())
+}
+
+async fn contract_metrics(
+    State(state): State<ServerState>,
+    Path((platform, id, name)): Path<(String, String, String)>,
+    Query(query): Query<ContractQuery>,
+) -> Result<Response, ApiError> {
+    let contract = stored_contract(&state, &platform, &id, &name, &query)?;
+    let metrics = contract
+        .metrics
+        .ok_or_else(|| ApiError::NotFound(format!("Metrics of {name} not found")))?;
+    Ok(Json(metrics).into_response())
+}
+
+async fn contract_imports(
+    State(state): State<ServerState>,
+    Path((platform, id, name)): Path<(String, String, String)>,
+    Query(query): Query<ContractQuery>,
+) -> ApiResult<ContractImports> {
+    let contract = stored_contract(&state, &platform, &id, &name, &query)?;
+    let graph = state
+        .store
+        .lock()
+        .expect("store lock poisoned")
+        .import_graph(parse_platform(&platform)?, &id, &contract.project_root)?
+        .ok_or_else(|| ApiError::NotFound(format!("Import graph of {name} not found")))?;
+    Ok(Json(ContractImports {
+        imports: graph
+            .direct_imports(&contract.source_file)
+            .into_iter()
+            .collect(),
+        transitive_imports: graph
+            .transitive_imports(&contract.source_file)
+            .into_iter()
+            .collect(),
+        project_root: contract.project_root,
+        source_file: contract.source_file,
+    }))
+}
+
+async fn refresh_contest(
+    State(state): State<ServerState>,
+    Path((platform, id)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let stored = stored_contest(&state, &platform, &id)?;
+    let platform = stored.contest.platform;
+
+    // builds take minutes, the dashboard polls the contest for the new build status. The clone
+    // and compilation are synchronous, so they run on a blocking thread
+    let pipeline = state.pipeline.clone();
+    let handle = Handle::current();
+    task::spawn_blocking(move || {
+        let mut pipeline = pipeline.blocking_lock();
+        match handle.block_on(pipeline.refresh_contest(platform, &id)) {
+            Ok(events) => info!(
+                "Refreshed contest {id} with {:?} events",
+                events.map(|e| e.len())
+            ),
+            Err(e) => error!("Refresh contest {id} error {e:?}"),
+        }
+    });
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "refreshing" })),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        analyzer::Metrics,
+        config::Config,
+        graph::ImportGraph,
+        types::{test_contest, ContractBuilder, ContractKind, ContractOrigin},
+    };
+    use ethers::abi::Abi;
+    use semver::Version;
+    use serde_json::Value;
+    use std::net::TcpListener;
+
+    fn test_state(similarity_index: PathBuf) -> ServerState {
+        let mut store = ContestStore::open_in_memory().unwrap();
+        let mut contest = test_contest();
+        store.upsert_contest(&contest).unwrap();
+        contest.contracts = vec![ContractBuilder::default()
+            .name("D3Vault".to_owned())
+            .kind(ContractKind::Interface)
+            .version(Version::new(0, 8, 16))
+            .source_file(PathBuf::from("contracts/D3Vault.sol"))
+            .in_scope(true)
+            .origin(ContractOrigin::Source)
+            .abi(Some(Abi::default()))
+            .metrics(Some(Metrics {
+                nsloc: 120,
+                ..Default::default()
+            }))
+            .build()
+            .unwrap()];
+        store
+            .record_build(&contest, Some("abc123"), &BuildStatus::Succeeded)
+            .unwrap();
+        let mut graph = ImportGraph::new("");
+        graph.add_edge("contracts/D3Vault.sol", "contracts/lib/Types.sol");
+        graph.add_edge("contracts/lib/Types.sol", "contracts/lib/Errors.sol");
+        store
+            .record_import_graphs(contest.platform, &contest.id, &[graph])
+            .unwrap();
+
+        let pipeline = ContestPipeline::new(
+            ContestStore::open_in_memory().unwrap(),
+            similarity_index,
+            vec![],
+            &Config::default(),
+        );
+        ServerState::new(store, pipeline)
+    }
+
+    #[tokio::test]
+    async fn test_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = router(test_state(dir.path().join("similarity_index.json")));
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let get = |path: &str| reqwest::get(format!("{base}{path}"));
+
+        let contests: Value = get("/contests?platform=sherlock&status=ongoing")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(contests.as_array().unwrap().len(), 1);
+        assert_eq!(contests[0]["build_status"], "succeeded");
+        let contests: Value = get("/contests?platform=code4rena")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(contests.as_array().unwrap().is_empty());
+        let response = get("/contests?status=judging").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let details: Value = get("/contests/sherlock/93")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(details["name"], "Dodo");
+        assert_eq!(details["repo_commit"], "abc123");
+        assert_eq!(details["contracts"][0]["name"], "D3Vault");
+        assert_eq!(details["contracts"][0]["nsloc"], 120);
+
+        let response = get("/contests/sherlock/93/contracts/D3Vault/abi")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let metrics: Value = get("/contests/sherlock/93/contracts/D3Vault/metrics")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(metrics["nsloc"], 120);
+        let imports: Value = get("/contests/sherlock/93/contracts/D3Vault/imports")
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(imports["imports"], json!(["contracts/lib/Types.sol"]));
+        assert_eq!(
+            imports["transitive_imports"],
+            json!(["contracts/lib/Errors.sol", "contracts/lib/Types.sol"])
+        );
+
+        for path in [
+            "/contests/sherlock/94",
+            "/contests/immunefi/93",
+            "/contests/sherlock/93/contracts/D3Oracle/abi",
+            "/contests/sherlock/93/contracts/D3Vault/abi?source_file=contracts/Other.sol",
+        ] {
+            assert_eq!(get(path).await.unwrap().status(), StatusCode::NOT_FOUND);
+        }
+        let response = reqwest::Client::new()
+            .post(format!("{base}/contests/sherlock/94/refresh"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
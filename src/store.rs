@@ -0,0 +1,707 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use ethers::abi::Abi;
+use rusqlite::{
+    params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Type, ValueRef},
+    Connection, OptionalExtension, Row, ToSql,
+};
+use semver::Version;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    analyzer::Metrics,
+    errors::AppError,
+    graph::ImportGraph,
+    types::{Contest, ContestPlatform, ContestStatus},
+};
+
+/// How long to wait for a lock held by another connection before failing with `SQLITE_BUSY`
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Schema of the first release, later changes are the [`MIGRATIONS`]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS contests (
+    id INTEGER PRIMARY KEY,
+    platform TEXT NOT NULL,
+    contest_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    repo_uri TEXT,
+    status TEXT NOT NULL,
+    repo_commit TEXT,
+    build_status TEXT NOT NULL DEFAULT 'pending',
+    build_error TEXT,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    built_at TEXT,
+    UNIQUE (platform, contest_id)
+);
+CREATE TABLE IF NOT EXISTS contracts (
+    id INTEGER PRIMARY KEY,
+    contest INTEGER NOT NULL REFERENCES contests (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    project_root TEXT NOT NULL,
+    source_file TEXT NOT NULL,
+    contract_type TEXT NOT NULL,
+    version TEXT NOT NULL,
+    in_scope INTEGER NOT NULL,
+    origin TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS contracts_contest ON contracts (contest);
+";
+
+/// Changes to [`SCHEMA`], the database `user_version` is the number of migrations applied
+const MIGRATIONS: &[Migration] = &[
+    Migration::AddColumns(
+        "contests",
+        &["start_date TEXT", "end_date TEXT", "reward TEXT"],
+    ),
+    Migration::Batch(
+        "
+CREATE TABLE IF NOT EXISTS contract_artifacts (
+    contract INTEGER PRIMARY KEY REFERENCES contracts (id) ON DELETE CASCADE,
+    abi TEXT,
+    metrics TEXT
+);
+CREATE TABLE IF NOT EXISTS import_graphs (
+    contest INTEGER NOT NULL REFERENCES contests (id) ON DELETE CASCADE,
+    project_root TEXT NOT NULL,
+    graph TEXT NOT NULL,
+    UNIQUE (contest, project_root)
+);
+",
+    ),
+];
+
+enum Migration {
+    /// Table and column definitions, columns that already exist are skipped since databases
+    /// created before `user_version` was set may have them
+    AddColumns(&'static str, &'static [&'static str]),
+    Batch(&'static str),
+}
+
+impl Migration {
+    fn apply(&self, conn: &Connection) -> Result<(), AppError> {
+        match self {
+            Self::AddColumns(table, columns) => {
+                let mut statement = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+                let existing = statement
+                    .query_map([], |row| row.get::<_, String>(1))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                for column in columns.iter() {
+                    let name = column.split_whitespace().next().unwrap_or_default();
+                    if !existing.iter().any(|existing| existing == name) {
+                        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column}"))?;
+                    }
+                }
+            }
+            Self::Batch(sql) => conn.execute_batch(sql)?,
+        }
+        Ok(())
+    }
+}
+
+const CONTEST_COLUMNS: &str = "platform, contest_id, name, description, uri, repo_uri, status, \
+    repo_commit, build_status, build_error, first_seen_at, last_seen_at, built_at, start_date, \
+    end_date, reward";
+
+/// Outcome of the last build of a contest repo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildStatus {
+    /// Not built yet
+    Pending,
+    Succeeded,
+    Failed(String),
+}
+
+impl BuildStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+/// A contest with the state kept across runs
+#[derive(Debug, Clone)]
+pub struct StoredContest {
+    /// The contest as last fetched, without its contracts, see [`ContestStore::contracts_for_contest`]
+    pub contest: Contest,
+    pub repo_commit: Option<String>,
+    pub build_status: BuildStatus,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub built_at: Option<DateTime<Utc>>,
+}
+
+/// A contract found by the last successful build of a contest
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredContract {
+    pub name: String,
+    pub project_root: PathBuf,
+    pub source_file: PathBuf,
+    /// [`crate::types::ContractType`] as formatted by `Debug`
+    pub contract_type: String,
+    pub version: Version,
+    pub in_scope: bool,
+    /// [`crate::types::ContractOrigin`] as formatted by `Debug`
+    pub origin: String,
+    pub abi: Option<Abi>,
+    pub metrics: Option<Metrics>,
+}
+
+/// SQLite store of the contests seen across runs. A contest is identified by its platform and
+/// its id on the platform.
+pub struct ContestStore {
+    conn: Connection,
+}
+
+impl ContestStore {
+    /// `contests.db` in the workspace directory, next to the cloned contest repos
+    pub fn default_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("contests.db")
+    }
+
+    pub fn open<P>(path: P) -> Result<Self, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, AppError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, AppError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // the server reads while the pipeline writes from another connection
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // in-memory databases keep their `memory` journal mode
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        Self::migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Create the schema, then apply the migrations the database is missing
+    fn migrate(conn: &mut Connection) -> Result<(), AppError> {
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        let version: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for migration in MIGRATIONS.iter().skip(version) {
+            migration.apply(&tx)?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Insert a fetched contest, or update the one seen in a previous run
+    pub fn upsert_contest(&self, contest: &Contest) -> Result<(), AppError> {
+        let now = Utc::now();
+        self.conn.execute(
+            "INSERT INTO contests (platform, contest_id, name, description, uri, repo_uri, status, \
+                first_seen_at, last_seen_at, start_date, end_date, reward)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8, ?9, ?10, ?11)
+            ON CONFLICT (platform, contest_id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                uri = excluded.uri,
+                repo_uri = excluded.repo_uri,
+                status = excluded.status,
+                last_seen_at = excluded.last_seen_at,
+                start_date = excluded.start_date,
+                end_date = excluded.end_date,
+                reward = excluded.reward",
+            params![
+                contest.platform,
+                contest.id,
+                contest.name,
+                contest.description,
+                contest.uri,
+                contest.repo_uri,
+                contest.status,
+                now,
+                contest.start_date,
+                contest.end_date,
+                contest.reward,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record a build of the contest repo. The contracts of a successful build replace the ones
+    /// stored before, a failed build keeps them.
+    pub fn record_build(
+        &mut self,
+        contest: &Contest,
+        repo_commit: Option<&str>,
+        status: &BuildStatus,
+    ) -> Result<(), AppError> {
+        let error = match status {
+            BuildStatus::Failed(error) => Some(error.as_str()),
+            _ => None,
+        };
+
+        let tx = self.conn.transaction()?;
+        let Some(contest_key) = tx
+            .query_row(
+                "SELECT id FROM contests WHERE platform = ?1 AND contest_id = ?2",
+                params![contest.platform, contest.id],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+        else {
+            return Err(AppError::UnknownError(anyhow::anyhow!(
+                "Contest {} {} not found in store",
+                contest.platform.as_str(),
+                contest.id
+            )));
+        };
+        tx.execute(
+            "UPDATE contests SET repo_commit = ?2, build_status = ?3, build_error = ?4, built_at = ?5
+            WHERE id = ?1",
+            params![contest_key, repo_commit, status.as_str(), error, Utc::now()],
+        )?;
+        if *status == BuildStatus::Succeeded {
+            tx.execute(
+                "DELETE FROM contracts WHERE contest = ?1",
+                params![contest_key],
+            )?;
+            let mut insert = tx.prepare(
+                "INSERT INTO contracts (contest, name, project_root, source_file, contract_type, \
+                    version, in_scope, origin)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut insert_artifacts = tx.prepare(
+                "INSERT INTO contract_artifacts (contract, abi, metrics) VALUES (?1, ?2, ?3)",
+            )?;
+            for contract in contest.contracts.iter() {
+                let contract_key = insert.insert(params![
+                    contest_key,
+                    contract.name,
+                    contract.project_root.to_string_lossy(),
+                    contract.source_file.to_string_lossy(),
+                    format!("{:?}", contract.contract_type),
+                    contract.version.to_string(),
+                    contract.in_scope,
+                    format!("{:?}", contract.origin),
+                ])?;
+                insert_artifacts.execute(params![
+                    contract_key,
+                    contract
+                        .abi
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    contract
+                        .metrics
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Update the status of a contest without marking it as seen, e.g. once it ended
+    pub fn set_status(
+        &self,
+        platform: ContestPlatform,
+        id: &str,
+        status: ContestStatus,
+    ) -> Result<(), AppError> {
+        self.conn.execute(
+            "UPDATE contests SET status = ?3 WHERE platform = ?1 AND contest_id = ?2",
+            params![platform, id, status],
+        )?;
+        Ok(())
+    }
+
+    pub fn contest(
+        &self,
+        platform: ContestPlatform,
+        id: &str,
+    ) -> Result<Option<StoredContest>, AppError> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {CONTEST_COLUMNS} FROM contests WHERE platform = ?1 AND contest_id = ?2"
+                ),
+                params![platform, id],
+                stored_contest,
+            )
+            .optional()?)
+    }
+
+    pub fn contests(&self) -> Result<Vec<StoredContest>, AppError> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {CONTEST_COLUMNS} FROM contests ORDER BY first_seen_at, id"
+        ))?;
+        let rows = statement.query_map([], stored_contest)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn contests_by_status(
+        &self,
+        status: ContestStatus,
+    ) -> Result<Vec<StoredContest>, AppError> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {CONTEST_COLUMNS} FROM contests WHERE status = ?1 ORDER BY first_seen_at, id"
+        ))?;
+        let rows = statement.query_map(params![status], stored_contest)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn contracts_for_contest(
+        &self,
+        platform: ContestPlatform,
+        id: &str,
+    ) -> Result<Vec<StoredContract>, AppError> {
+        let mut statement = self.conn.prepare(
+            "SELECT contracts.name, project_root, source_file, contract_type, version, in_scope, \
+                origin, abi, metrics
+            FROM contracts JOIN contests ON contracts.contest = contests.id
+            LEFT JOIN contract_artifacts ON contract_artifacts.contract = contracts.id
+            WHERE contests.platform = ?1 AND contests.contest_id = ?2
+            ORDER BY contracts.id",
+        )?;
+        let rows = statement.query_map(params![platform, id], |row| {
+            let version: String = row.get(4)?;
+            Ok(StoredContract {
+                name: row.get(0)?,
+                project_root: PathBuf::from(row.get::<_, String>(1)?),
+                source_file: PathBuf::from(row.get::<_, String>(2)?),
+                contract_type: row.get(3)?,
+                version: Version::parse(&version).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e))
+                })?,
+                in_scope: row.get(5)?,
+                origin: row.get(6)?,
+                abi: json_column(row, 7)?,
+                metrics: json_column(row, 8)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Replace the import graphs of a contest with the ones of its last build
+    pub fn record_import_graphs(
+        &mut self,
+        platform: ContestPlatform,
+        id: &str,
+        graphs: &[ImportGraph],
+    ) -> Result<(), AppError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM import_graphs
+            WHERE contest = (SELECT id FROM contests WHERE platform = ?1 AND contest_id = ?2)",
+            params![platform, id],
+        )?;
+        for graph in graphs.iter() {
+            tx.execute(
+                "INSERT INTO import_graphs (contest, project_root, graph)
+                SELECT id, ?3, ?4 FROM contests WHERE platform = ?1 AND contest_id = ?2",
+                params![
+                    platform,
+                    id,
+                    graph.project_root.to_string_lossy(),
+                    serde_json::to_string(graph)?
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Import graph of a project root of the contest
+    pub fn import_graph<P>(
+        &self,
+        platform: ContestPlatform,
+        id: &str,
+        project_root: P,
+    ) -> Result<Option<ImportGraph>, AppError>
+    where
+        P: AsRef<Path>,
+    {
+        let graph: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT graph FROM import_graphs JOIN contests ON import_graphs.contest = contests.id
+                WHERE contests.platform = ?1 AND contests.contest_id = ?2
+                    AND import_graphs.project_root = ?3",
+                params![platform, id, project_root.as_ref().to_string_lossy()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(graph
+            .map(|graph| serde_json::from_str(&graph))
+            .transpose()?)
+    }
+}
+
+/// Deserialize a nullable JSON column
+fn json_column<T>(row: &Row, index: usize) -> rusqlite::Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    row.get::<_, Option<String>>(index)?
+        .map(|json| {
+            serde_json::from_str(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e))
+            })
+        })
+        .transpose()
+}
+
+/// Map a row selected with [`CONTEST_COLUMNS`]
+fn stored_contest(row: &Row) -> rusqlite::Result<StoredContest> {
+    let build_status = match row.get::<_, String>(8)?.as_str() {
+        "succeeded" => BuildStatus::Succeeded,
+        "failed" => BuildStatus::Failed(row.get::<_, Option<String>>(9)?.unwrap_or_default()),
+        _ => BuildStatus::Pending,
+    };
+    Ok(StoredContest {
+        contest: Contest {
+            platform: row.get(0)?,
+            id: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            uri: row.get(4)?,
+            repo_uri: row.get(5)?,
+            status: row.get(6)?,
+            start_date: row.get(13)?,
+            end_date: row.get(14)?,
+            reward: row.get(15)?,
+            contracts: vec![],
+        },
+        repo_commit: row.get(7)?,
+        build_status,
+        first_seen_at: row.get(10)?,
+        last_seen_at: row.get(11)?,
+        built_at: row.get(12)?,
+    })
+}
+
+impl ToSql for ContestPlatform {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ContestPlatform {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "code4rena" => Ok(Self::Code4rena),
+            "sherlock" => Ok(Self::Sherlock),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for ContestStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ContestStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "ongoing" => Ok(Self::Ongoing),
+            "upcoming" => Ok(Self::Upcoming),
+            "ended" => Ok(Self::Ended),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{ContractBuilder, ContractKind, ContractOrigin};
+
+    fn contest(id: &str, status: ContestStatus) -> Contest {
+        Contest {
+            platform: ContestPlatform::Code4rena,
+            id: id.to_owned(),
+            name: id.to_owned(),
+            description: String::new(),
+            uri: format!("https://code4rena.com/contests/{id}"),
+            repo_uri: Some(format!("https://github.com/code-423n4/{id}")),
+            status,
+            start_date: None,
+            end_date: None,
+            reward: None,
+            contracts: vec![],
+        }
+    }
+
+    #[test]
+    fn test_contest_store() {
+        let mut store = ContestStore::open_in_memory().unwrap();
+        let mut maia = contest("2023-05-maia", ContestStatus::Upcoming);
+        store.upsert_contest(&maia).unwrap();
+        store
+            .upsert_contest(&contest("2023-06-lybra", ContestStatus::Ongoing))
+            .unwrap();
+
+        // seen again in a later run, now started
+        maia.status = ContestStatus::Ongoing;
+        store.upsert_contest(&maia).unwrap();
+        assert_eq!(store.contests().unwrap().len(), 2);
+        assert_eq!(
+            store
+                .contests_by_status(ContestStatus::Ongoing)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(store
+            .contests_by_status(ContestStatus::Upcoming)
+            .unwrap()
+            .is_empty());
+
+        maia.contracts = vec![ContractBuilder::default()
+            .name("Vault".to_owned())
+            .kind(ContractKind::Interface)
+            .version(Version::new(0, 8, 19))
+            .source_file(PathBuf::from("src/Vault.sol"))
+            .in_scope(true)
+            .origin(ContractOrigin::Source)
+            .abi(Some(Abi::default()))
+            .metrics(Some(Metrics {
+                nsloc: 42,
+                ..Default::default()
+            }))
+            .build()
+            .unwrap()];
+        store
+            .record_build(&maia, Some("abc123"), &BuildStatus::Succeeded)
+            .unwrap();
+        store
+            .record_build(
+                &maia,
+                Some("def456"),
+                &BuildStatus::Failed("solc".to_owned()),
+            )
+            .unwrap();
+
+        let stored = store
+            .contest(ContestPlatform::Code4rena, "2023-05-maia")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.contest.status, ContestStatus::Ongoing);
+        assert_eq!(stored.repo_commit.as_deref(), Some("def456"));
+        assert_eq!(stored.build_status, BuildStatus::Failed("solc".to_owned()));
+        assert!(stored.built_at.is_some());
+        assert!(stored.first_seen_at <= stored.last_seen_at);
+
+        // the failed build keeps the contracts of the last successful one
+        let contracts = store
+            .contracts_for_contest(ContestPlatform::Code4rena, "2023-05-maia")
+            .unwrap();
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].name, "Vault");
+        assert_eq!(contracts[0].source_file, PathBuf::from("src/Vault.sol"));
+        assert_eq!(contracts[0].origin, "Source");
+        assert!(contracts[0].in_scope);
+        assert_eq!(contracts[0].abi, Some(Abi::default()));
+        assert_eq!(contracts[0].metrics.map(|metrics| metrics.nsloc), Some(42));
+
+        let mut graph = ImportGraph::new("");
+        graph.add_edge("src/Vault.sol", "src/IERC20.sol");
+        for _ in 0..2 {
+            store
+                .record_import_graphs(ContestPlatform::Code4rena, "2023-05-maia", &[graph.clone()])
+                .unwrap();
+        }
+        assert_eq!(
+            store
+                .import_graph(ContestPlatform::Code4rena, "2023-05-maia", "")
+                .unwrap(),
+            Some(graph)
+        );
+        assert!(store
+            .import_graph(ContestPlatform::Code4rena, "2023-06-lybra", "")
+            .unwrap()
+            .is_none());
+        assert!(store
+            .contest(ContestPlatform::Sherlock, "2023-05-maia")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_migrate_first_release_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("contests.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(SCHEMA).unwrap();
+            conn.execute(
+                "INSERT INTO contests (platform, contest_id, name, description, uri, status, \
+                first_seen_at, last_seen_at) VALUES ('sherlock', '93', 'Dodo', '', 'uri', \
+                'ongoing', ?1, ?1)",
+                params![Utc::now()],
+            )
+            .unwrap();
+        }
+
+        let store = ContestStore::open(&path).unwrap();
+        let contests = store.contests().unwrap();
+        assert_eq!(contests.len(), 1);
+        assert_eq!(contests[0].contest.reward, None);
+        assert!(store
+            .import_graph(ContestPlatform::Sherlock, "93", "")
+            .unwrap()
+            .is_none());
+        let version: usize = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        drop(store);
+
+        // migrations already applied are skipped
+        assert_eq!(
+            ContestStore::open(&path).unwrap().contests().unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_read_while_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("contests.db");
+        let writer = ContestStore::open(&path).unwrap();
+        let reader = ContestStore::open(&path).unwrap();
+        let journal_mode: String = reader
+            .conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        writer.conn.execute_batch("BEGIN EXCLUSIVE").unwrap();
+        writer
+            .upsert_contest(&contest("2023-05-maia", ContestStatus::Ongoing))
+            .unwrap();
+        assert!(reader.contests().unwrap().is_empty());
+        writer.conn.execute_batch("COMMIT").unwrap();
+        assert_eq!(reader.contests().unwrap().len(), 1);
+    }
+}
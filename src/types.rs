@@ -4,3 +4,7 @@ mod config;
 pub use config::*;
 mod contract;
 pub use contract::*;
+mod library;
+pub use library::*;
+mod scope;
+pub use scope::*;
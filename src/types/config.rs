@@ -15,6 +15,7 @@ pub struct FoundryConfigProfileDefault {
     pub src: Option<String>,
     pub libs: Option<Vec<String>>,
     pub test: Option<String>,
+    pub script: Option<String>,
     pub cache_path: Option<String>,
     pub out: Option<String>,
 }
@@ -1,32 +1,227 @@
 use super::Contract;
+use crate::analyzer::Metrics;
+use chrono::{DateTime, SecondsFormat, Utc};
 use derive_builder::Builder;
 use derive_more::{From, Into};
-use std::path::Path;
+use std::{collections::HashSet, path::Path, str::FromStr};
 
 #[derive(Debug, Clone, Builder)]
 pub struct Contest {
+    pub platform: ContestPlatform,
+    /// Identifier of the contest on its platform, unique together with [`Self::platform`]
+    pub id: String,
     pub name: String,
     pub description: String,
     pub uri: String,
     pub repo_uri: Option<String>,
     pub status: ContestStatus,
-    // start_date: DateTime<Utc>,
-    // end_date: DateTime<Utc>,
-    // reward: String,
+    #[builder(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[builder(default)]
+    pub end_date: Option<DateTime<Utc>>,
+    /// Prize pool as advertised by the platform, e.g. `$65,500 USDC`
+    #[builder(default)]
+    pub reward: Option<String>,
     pub contracts: Vec<Contract>,
 }
 
-#[derive(Debug, Clone)]
+/// Metrics of a contest, summed over its contracts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContestMetrics {
+    pub contracts: usize,
+    pub in_scope_contracts: usize,
+    pub total: Metrics,
+    pub in_scope: Metrics,
+}
+
+impl Contest {
+    /// Metrics summed once per contract, contracts compiled with several solc versions are
+    /// found once per version
+    pub fn metrics(&self) -> ContestMetrics {
+        let mut result = ContestMetrics::default();
+        let mut seen: HashSet<(&Path, &Path, &str)> = HashSet::new();
+        for contract in self.contracts.iter() {
+            let key = (
+                contract.project_root.as_path(),
+                contract.source_file.as_path(),
+                contract.name.as_str(),
+            );
+            if !seen.insert(key) {
+                continue;
+            }
+            let metrics = contract.metrics.unwrap_or_default();
+            result.contracts += 1;
+            result.total += metrics;
+            if contract.in_scope {
+                result.in_scope_contracts += 1;
+                result.in_scope += metrics;
+            }
+        }
+        result
+    }
+}
+
+/// Sherlock contest used across the tests
+#[cfg(test)]
+pub(crate) fn test_contest() -> Contest {
+    use chrono::TimeZone;
+
+    Contest {
+        platform: ContestPlatform::Sherlock,
+        id: "93".to_owned(),
+        name: "Dodo".to_owned(),
+        description: "Dodo V3".to_owned(),
+        uri: "https://app.sherlock.xyz/audits/contests/93".to_owned(),
+        repo_uri: Some("https://github.com/sherlock-audit/2023-06-dodo".to_owned()),
+        status: ContestStatus::Ongoing,
+        start_date: Utc.with_ymd_and_hms(2023, 6, 27, 15, 0, 0).single(),
+        end_date: Utc.with_ymd_and_hms(2023, 7, 1, 15, 0, 0).single(),
+        reward: Some("$56,000 USDC".to_owned()),
+        contracts: vec![],
+    }
+}
+
+/// How contest dates are written by the notifications, the API, the feeds and the calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `2023-06-27 15:00 UTC`
+    Text,
+    /// `2023-06-27T15:00:00Z`
+    Rfc3339,
+    /// `20230627T150000Z`, an iCalendar UTC date-time
+    ICalendar,
+}
+
+impl DateFormat {
+    pub fn format(&self, date: &DateTime<Utc>) -> String {
+        match self {
+            Self::Text => date.format("%Y-%m-%d %H:%M UTC").to_string(),
+            Self::Rfc3339 => date.to_rfc3339_opts(SecondsFormat::Secs, true),
+            Self::ICalendar => date.format("%Y%m%dT%H%M%SZ").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContestStatus {
     Ongoing,
     Upcoming,
+    /// No longer listed by its platform
+    Ended,
+}
+
+impl ContestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ongoing => "ongoing",
+            Self::Upcoming => "upcoming",
+            Self::Ended => "ended",
+        }
+    }
+}
+
+impl FromStr for ContestStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Ongoing, Self::Upcoming, Self::Ended]
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown contest status {s:?}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContestPlatform {
+    Code4rena,
+    Sherlock,
+}
+
+impl ContestPlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Code4rena => "code4rena",
+            Self::Sherlock => "sherlock",
+        }
+    }
+}
+
+impl FromStr for ContestPlatform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Code4rena, Self::Sherlock]
+            .into_iter()
+            .find(|platform| platform.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown contest platform {s:?}"))
+    }
 }
 
 #[derive(Debug, From, Into)]
 pub struct RepoUri(String);
 
 impl RepoUri {
+    /// Last path segment of the uri, `None` for e.g. an empty uri or one ending with `..`
     pub fn to_dir_name(&self) -> Option<&str> {
-        Path::new(&self.0).file_name().unwrap().to_str()
+        Path::new(&self.0).file_name()?.to_str()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use semver::Version;
+
+    use super::*;
+    use crate::{
+        analyzer::Metrics,
+        types::{ContractBuilder, ContractKind},
+    };
+
+    #[test]
+    fn test_repo_uri_dir_name() {
+        let dir_name = |uri: &str| {
+            RepoUri::from(uri.to_owned())
+                .to_dir_name()
+                .map(str::to_owned)
+        };
+        assert_eq!(
+            dir_name("https://github.com/code-423n4/2023-05-maia").as_deref(),
+            Some("2023-05-maia")
+        );
+        assert_eq!(dir_name("https://github.com/code-423n4/.."), None);
+        assert_eq!(dir_name(""), None);
+    }
+
+    #[test]
+    fn test_contest_metrics() {
+        let contract = |name: &str, version: Version, in_scope: bool| {
+            ContractBuilder::default()
+                .name(name.to_owned())
+                .kind(ContractKind::Interface)
+                .version(version)
+                .source_file(format!("src/{name}.sol").into())
+                .in_scope(in_scope)
+                .metrics(Some(Metrics {
+                    nsloc: 100,
+                    ..Default::default()
+                }))
+                .build()
+                .unwrap()
+        };
+        let contest = Contest {
+            contracts: vec![
+                contract("Vault", Version::new(0, 8, 19), true),
+                // same file compiled with another solc version
+                contract("Vault", Version::new(0, 8, 20), true),
+                contract("Token", Version::new(0, 8, 19), false),
+            ],
+            ..test_contest()
+        };
+
+        let metrics = contest.metrics();
+        assert_eq!(metrics.contracts, 2);
+        assert_eq!(metrics.in_scope_contracts, 1);
+        assert_eq!(metrics.total.nsloc, 200);
+        assert_eq!(metrics.in_scope.nsloc, 100);
     }
 }
@@ -1,11 +1,18 @@
-use crate::errors::{AppError, ContractError};
+use crate::{
+    analyzer::{
+        CallSurface, Inheritance, Metrics, SelectorTable, StandardMatch, StorageLayout,
+        Upgradeability,
+    },
+    errors::{AppError, ContractError},
+};
 use chrono::{DateTime, Utc};
 use derive_builder::Builder;
-use ethers::types::Bytes;
+use ethers::{abi::Abi, types::Bytes};
 use ethers_solc::{
     artifacts::NodeType,
     cache::{CacheEntry, SolFilesCache},
     ArtifactOutput, ConfigurableArtifacts, ConfigurableContractArtifact, Project,
+    ProjectPathsConfig,
 };
 use rayon::prelude::*;
 use rr_logging::info;
@@ -25,33 +32,141 @@ pub struct Contract {
     pub name: String,
     // pub bytecode: ContractBytecode,
     pub kind: ContractKind,
+    #[builder(default)]
+    pub contract_type: ContractType,
     pub version: Version,
     #[builder(default)]
+    pub project_root: PathBuf,
+    /// Source file, relative to the project root
+    #[builder(default)]
+    pub source_file: PathBuf,
+    #[builder(default)]
+    pub artifact_file: PathBuf,
+    #[builder(default)]
+    pub abi: Option<Abi>,
+    /// Hash of the solc settings the source file was compiled with
+    #[builder(default)]
+    pub settings_hash: String,
+    #[builder(default)]
     pub imported_contracts: Vec<ContractFromArtifact>,
+    #[builder(default)]
+    pub inheritance: Option<Inheritance>,
+    #[builder(default)]
+    pub metrics: Option<Metrics>,
+    #[builder(default)]
+    pub selectors: Option<SelectorTable>,
+    #[builder(default)]
+    pub storage_layout: Option<StorageLayout>,
+    #[builder(default)]
+    pub upgradeability: Option<Upgradeability>,
+    #[builder(default)]
+    pub call_surface: Option<CallSurface>,
+    /// Token and interface standards matched by the ABI
+    #[builder(default)]
+    pub standards: Vec<StandardMatch>,
+    /// Whether the contract's source file is declared in the contest scope
+    #[builder(default)]
+    pub in_scope: bool,
+    #[builder(default)]
+    pub origin: ContractOrigin,
+    /// Package name of the third-party library the contract comes from
+    #[builder(default)]
+    pub library: Option<String>,
+    /// nSLOC of the source file as declared by the contest
+    #[builder(default)]
+    pub declared_nsloc: Option<usize>,
 }
 
 impl PartialOrd for Contract {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if matches!(self.kind, ContractKind::Interface)
-            && matches!(other.kind, ContractKind::Contract(_))
-        {
-            Some(Ordering::Less)
-        } else if matches!(self.kind, ContractKind::Interface)
-            && matches!(other.kind, ContractKind::Interface)
-        {
-            Some(Ordering::Equal)
-        } else {
-            Some(Ordering::Greater)
-        }
+        Some(self.cmp(other))
     }
 }
 
+/// Interfaces first, then by name. Use [`crate::analyzer::sort_by_reading_order`] to order
+/// contracts by their dependencies.
+impl Ord for Contract {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind
+            .rank()
+            .cmp(&other.kind.rank())
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.kind.cmp(&other.kind))
+    }
+}
+
+impl Eq for Contract {}
+
 impl PartialEq for Contract {
     fn eq(&self, other: &Self) -> bool {
         self.kind == other.kind && self.name == other.name
     }
 }
 
+/// Where the source file of a contract lives in the project
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ContractOrigin {
+    Source,
+    Test,
+    Script,
+    Mock,
+    Library,
+    #[default]
+    Unknown,
+}
+
+impl ContractOrigin {
+    /// Classify a source file using the sources, tests, scripts and libs of the project
+    pub fn classify<P>(source_file: P, paths: &ProjectPathsConfig) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let source_file = source_file.as_ref();
+        let file_name = source_file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if paths
+            .libraries
+            .iter()
+            .any(|lib| source_file.starts_with(lib))
+        {
+            return Self::Library;
+        }
+        if file_name.ends_with(".t.sol") || source_file.starts_with(&paths.tests) {
+            return Self::Test;
+        }
+        if file_name.ends_with(".s.sol") || source_file.starts_with(&paths.scripts) {
+            return Self::Script;
+        }
+        if source_file.starts_with(&paths.sources) {
+            // only directories inside the project, a checkout under `~/mocks` isn't a mock
+            let relative_file = source_file.strip_prefix(&paths.root).unwrap_or(source_file);
+            if Self::is_mock(relative_file) {
+                return Self::Mock;
+            }
+            return Self::Source;
+        }
+        Self::Unknown
+    }
+
+    fn is_mock(source_file: &Path) -> bool {
+        let in_mock_dir = source_file.parent().is_some_and(|dir| {
+            dir.components().any(|component| {
+                let name = component.as_os_str().to_string_lossy().to_lowercase();
+                name == "mock" || name == "mocks"
+            })
+        });
+        let stem = source_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        in_mock_dir || stem.starts_with("Mock") || stem.ends_with("Mock")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ContractFromArtifact {
     pub name: String,
@@ -61,20 +176,23 @@ pub struct ContractFromArtifact {
 
 impl PartialOrd for ContractFromArtifact {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if matches!(self.kind, ContractKind::Interface)
-            && matches!(other.kind, ContractKind::Contract(_))
-        {
-            Some(Ordering::Less)
-        } else if matches!(self.kind, ContractKind::Interface)
-            && matches!(other.kind, ContractKind::Interface)
-        {
-            Some(Ordering::Equal)
-        } else {
-            Some(Ordering::Greater)
-        }
+        Some(self.cmp(other))
+    }
+}
+
+/// Interfaces first, then by name
+impl Ord for ContractFromArtifact {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind
+            .rank()
+            .cmp(&other.kind.rank())
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.kind.cmp(&other.kind))
     }
 }
 
+impl Eq for ContractFromArtifact {}
+
 impl PartialEq for ContractFromArtifact {
     fn eq(&self, other: &Self) -> bool {
         self.kind == other.kind && self.name == other.name
@@ -111,12 +229,59 @@ impl std::fmt::Debug for ContractBytecode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ContractKind {
     Interface,
     Contract(ContractBytecode),
 }
 
+impl ContractKind {
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Interface => 0,
+            Self::Contract(_) => 1,
+        }
+    }
+}
+
+/// Kind of a contract definition as declared in the source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContractType {
+    Interface,
+    Library,
+    Abstract,
+    #[default]
+    Contract,
+}
+
+impl ContractType {
+    /// Read the type from the `contractKind` and `abstract` attributes of a `ContractDefinition`
+    pub fn from_definition(definition: &serde_json::Value) -> Self {
+        let is_abstract = definition
+            .get("abstract")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        match definition
+            .get("contractKind")
+            .and_then(|kind| kind.as_str())
+        {
+            Some("interface") => Self::Interface,
+            Some("library") => Self::Library,
+            _ if is_abstract => Self::Abstract,
+            _ => Self::Contract,
+        }
+    }
+}
+
+impl From<&ContractKind> for ContractType {
+    fn from(value: &ContractKind) -> Self {
+        match value {
+            ContractKind::Interface => Self::Interface,
+            ContractKind::Contract(_) => Self::Contract,
+        }
+    }
+}
+
 impl From<ContractBytecode> for ContractKind {
     fn from(value: ContractBytecode) -> Self {
         if value.0 == "0x" {
@@ -126,3 +291,52 @@ impl From<ContractBytecode> for ContractKind {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_contract_origin_classify() {
+        let paths = ProjectPathsConfig::builder()
+            .root("/repo")
+            .sources("/repo/src")
+            .tests("/repo/test")
+            .scripts("/repo/script")
+            .libs(vec![PathBuf::from("/repo/lib")])
+            .build()
+            .unwrap();
+
+        let cases = [
+            ("/repo/src/Vault.sol", ContractOrigin::Source),
+            ("/repo/src/mocks/ERC20.sol", ContractOrigin::Mock),
+            ("/repo/src/MockOracle.sol", ContractOrigin::Mock),
+            ("/repo/src/Vault.t.sol", ContractOrigin::Test),
+            ("/repo/test/Vault.sol", ContractOrigin::Test),
+            ("/repo/script/Deploy.s.sol", ContractOrigin::Script),
+            ("/repo/lib/forge-std/src/Test.sol", ContractOrigin::Library),
+            ("/elsewhere/Foo.sol", ContractOrigin::Unknown),
+        ];
+        for (file, expected) in cases {
+            assert_eq!(ContractOrigin::classify(file, &paths), expected, "{}", file);
+        }
+    }
+
+    #[test]
+    fn test_contract_origin_classify_under_mocks_dir() {
+        let paths = ProjectPathsConfig::builder()
+            .root("/home/auditor/mocks/repo")
+            .sources("/home/auditor/mocks/repo/src")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            ContractOrigin::classify("/home/auditor/mocks/repo/src/Vault.sol", &paths),
+            ContractOrigin::Source
+        );
+        assert_eq!(
+            ContractOrigin::classify("/home/auditor/mocks/repo/src/mocks/ERC20.sol", &paths),
+            ContractOrigin::Mock
+        );
+    }
+}
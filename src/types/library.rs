@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// How a third-party library ended up in the project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum LibrarySource {
+    /// A git submodule, usually under `lib/`, checked out at `commit`
+    GitSubmodule { commit: Option<String> },
+    /// An npm package under `node_modules/`
+    NodeModules,
+    /// Sources copied into the repo
+    Vendored,
+}
+
+/// Where the version of a library was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum VersionSource {
+    /// `version` of the library's `package.json`
+    PackageJson,
+    /// Latest tag of a git submodule
+    GitTag,
+    /// Highest `last updated vX` file header, a lower bound of the real version
+    Header,
+}
+
+/// Files of a library copy changed by the sponsor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ModifiedFiles {
+    /// Compared with git or with another copy of the same version
+    Checked(Vec<PathBuf>),
+    /// A vendored copy with nothing to compare against, it may be modified
+    Unknown,
+}
+
+/// A copy of a third-party library used by a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Library {
+    /// Package name, e.g. `@openzeppelin/contracts` or `solmate`
+    pub name: String,
+    pub version: Option<Version>,
+    pub version_source: Option<VersionSource>,
+    pub project_root: PathBuf,
+    /// Root directory of the copy, relative to the project root
+    pub path: PathBuf,
+    pub source: LibrarySource,
+    /// Files of the library used by the project, relative to the project root
+    pub files: Vec<PathBuf>,
+    /// keccak256 over the path and content of every file, to recognize identical copies
+    pub source_hash: String,
+    pub modified_files: ModifiedFiles,
+}
+
+impl Library {
+    /// Whether files are known to be changed, `false` when it can't be checked
+    pub fn is_modified(&self) -> bool {
+        matches!(&self.modified_files, ModifiedFiles::Checked(files) if !files.is_empty())
+    }
+
+    /// Whether the version is only a lower bound read from file headers
+    pub fn is_version_approximate(&self) -> bool {
+        self.version_source == Some(VersionSource::Header)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    /// Package name, as in [`Library::name`]
+    pub library: String,
+    /// Affected versions, e.g. `>=4.3.0, <4.9.1`
+    pub affected: String,
+    pub severity: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl Advisory {
+    /// Whether the advisory applies to the library. Libraries of unknown version never match.
+    pub fn affects(&self, library: &Library) -> bool {
+        let (Some(version), Ok(affected)) = (&library.version, VersionReq::parse(&self.affected))
+        else {
+            return false;
+        };
+        self.library == library.name && affected.matches(version)
+    }
+}
+
+/// An advisory affecting a library of a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AdvisoryMatch {
+    pub project_root: PathBuf,
+    pub library: String,
+    pub version: Version,
+    /// The version was read from file headers and may be older than the real one
+    pub approximate_version: bool,
+    pub advisory: Advisory,
+}
@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+/// Files declared in scope by the contest, e.g. in `scope.txt` or the README scope table
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    /// Repo directory the entries are relative to
+    pub repo_dir: PathBuf,
+    pub entries: Vec<ScopeEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeEntry {
+    /// Path as declared by the contest, relative to the repo directory
+    pub path: PathBuf,
+    pub nsloc: Option<usize>,
+}
+
+impl Scope {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the scope entry of a source file, either absolute or relative to the repo directory.
+    /// Paths are compared exactly, so dependencies sharing a suffix with an entry don't match.
+    pub fn find<P>(&self, source_file: P) -> Option<&ScopeEntry>
+    where
+        P: AsRef<Path>,
+    {
+        let source_file = source_file.as_ref();
+        let relative_path = if source_file.is_absolute() {
+            source_file.strip_prefix(&self.repo_dir).ok()?
+        } else {
+            source_file
+        };
+        self.entries
+            .iter()
+            .find(|entry| relative_path == entry.path)
+    }
+
+    /// Add an entry, or fill in the missing nSLOC of an existing one
+    pub fn merge(&mut self, entry: ScopeEntry) {
+        match self.entries.iter_mut().find(|item| item.path == entry.path) {
+            Some(existing) => {
+                if existing.nsloc.is_none() {
+                    existing.nsloc = entry.nsloc;
+                }
+            }
+            None => self.entries.push(entry),
+        }
+    }
+}